@@ -0,0 +1,21 @@
+use advent_2023::bench::{count_possible, count_possible_ordered, parse_input};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const INPUT: &str = include_str!("../src/day2/input.txt");
+
+fn can_contain(c: &mut Criterion) {
+    let games = parse_input(INPUT);
+    assert_eq!(count_possible(&games), count_possible_ordered(&games));
+
+    let mut group = c.benchmark_group("fits_in");
+    group.bench_function("map_order", |b| {
+        b.iter(|| count_possible(black_box(&games)))
+    });
+    group.bench_function("color_order", |b| {
+        b.iter(|| count_possible_ordered(black_box(&games)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, can_contain);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+use advent_2023::bench::parse_input;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const INPUT: &str = include_str!("../src/day2/input.txt");
+
+fn parse(c: &mut Criterion) {
+    c.bench_function("parse_input", |b| b.iter(|| parse_input(black_box(INPUT))));
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
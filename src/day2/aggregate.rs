@@ -0,0 +1,110 @@
+use super::dice::Bag;
+use super::input::NumberedGame;
+
+/// Reduces games to a single value, one game at a time, starting from
+/// `identity`.
+pub(crate) trait GameAggregator {
+    type Output;
+
+    fn fold(&self, acc: Self::Output, game: &NumberedGame) -> Self::Output;
+    fn identity(&self) -> Self::Output;
+}
+
+/// Sums the power of each game's requirements, as in part 2, saturating at
+/// `u64::MAX`.
+pub(crate) struct PowerSum;
+
+/// Sums the ids of the games possible with the bag, as in part 1, saturating
+/// at `u64::MAX`.
+pub(crate) struct PossibleIdSum(pub(crate) Bag);
+
+impl GameAggregator for PowerSum {
+    type Output = u64;
+
+    fn fold(&self, acc: u64, game: &NumberedGame) -> u64 {
+        acc.saturating_add(game.get_power())
+    }
+
+    fn identity(&self) -> u64 {
+        0
+    }
+}
+
+impl GameAggregator for PossibleIdSum {
+    type Output = u64;
+
+    fn fold(&self, acc: u64, game: &NumberedGame) -> u64 {
+        if game.is_possible_for(&self.0) {
+            acc.saturating_add(game.id)
+        } else {
+            acc
+        }
+    }
+
+    fn identity(&self) -> u64 {
+        0
+    }
+}
+
+pub(crate) fn aggregate<A: GameAggregator>(games: &[NumberedGame], aggregator: A) -> A::Output {
+    games.iter().fold(aggregator.identity(), |acc, game| {
+        aggregator.fold(acc, game)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dice::Bag;
+    use super::super::input::{parse_input, NumberedGame};
+    use super::super::{DAY2_PART1_ANSWER, DAY2_PART2_ANSWER};
+    use super::{aggregate, GameAggregator, PossibleIdSum, PowerSum};
+
+    #[test]
+    fn reproduces_both_answers() {
+        let games = parse_input(include_str!("input.txt")).unwrap();
+
+        assert_eq!(
+            DAY2_PART1_ANSWER,
+            aggregate(&games, PossibleIdSum(Bag::standard()))
+        );
+        assert_eq!(DAY2_PART2_ANSWER, aggregate(&games, PowerSum));
+        assert_eq!(0, aggregate(&[], PowerSum));
+    }
+
+    #[test]
+    fn saturates_instead_of_overflowing() {
+        let large_ids = parse_input(&format!("Game {}: 1 red\nGame 1: 1 red", u64::MAX)).unwrap();
+        assert_eq!(
+            u64::MAX,
+            aggregate(&large_ids, PossibleIdSum(Bag::standard()))
+        );
+
+        let large_powers = parse_input(
+            "Game 1: 4294967295 red, 4294967295 green, 1 blue
+            Game 2: 4294967295 red, 4294967295 green, 1 blue",
+        )
+        .unwrap();
+        assert_eq!(u64::MAX, aggregate(&large_powers, PowerSum));
+    }
+
+    #[test]
+    fn aggregates_custom_metrics() {
+        struct MaxId;
+
+        impl GameAggregator for MaxId {
+            type Output = Option<u64>;
+
+            fn fold(&self, acc: Option<u64>, game: &NumberedGame) -> Option<u64> {
+                acc.max(Some(game.id))
+            }
+
+            fn identity(&self) -> Option<u64> {
+                None
+            }
+        }
+
+        let games = parse_input("Game 3: 1 red\nGame 7: 2 blue\nGame 5: 1 green").unwrap();
+        assert_eq!(Some(7), aggregate(&games, MaxId));
+        assert_eq!(None, aggregate(&[], MaxId));
+    }
+}
@@ -0,0 +1,479 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::dice::{Bag, Color};
+use super::input::NumberedGame;
+
+/// The game needing the most powerful bag, preferring the lowest id on ties.
+pub(crate) fn max_power_game(games: &[NumberedGame]) -> Option<&NumberedGame> {
+    games
+        .iter()
+        .max_by_key(|game| (game.get_power(), Reverse(game.id)))
+}
+
+/// Splits the games into the ones possible with the bag, and the ones that aren't.
+pub(crate) fn partition_by_possibility<'a>(
+    games: &'a [NumberedGame],
+    bag: &Bag,
+) -> (Vec<&'a NumberedGame>, Vec<&'a NumberedGame>) {
+    games.iter().partition(|game| game.is_possible_for(bag))
+}
+
+/// The ids missing from the range spanning the lowest to the highest game id.
+pub(crate) fn find_id_gaps(games: &[NumberedGame]) -> Vec<u64> {
+    let ids: HashSet<u64> = games.iter().map(|game| game.id).collect();
+    let (Some(&min_id), Some(&max_id)) = (ids.iter().min(), ids.iter().max()) else {
+        return vec![];
+    };
+
+    (min_id..=max_id).filter(|id| !ids.contains(id)).collect()
+}
+
+/// The ids of the games, sorted and without duplicates.
+pub(crate) fn distinct_ids(games: &[NumberedGame]) -> Vec<u64> {
+    let mut ids: Vec<u64> = games.iter().map(|game| game.id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    ids
+}
+
+/// For each color, the ids of the games that need more of it than the bag holds.
+/// A game short of several colors is listed under each of them.
+pub(crate) fn group_by_color_violation(
+    games: &[NumberedGame],
+    bag: &Bag,
+) -> HashMap<Color, Vec<u64>> {
+    let mut groups: HashMap<Color, Vec<u64>> = HashMap::new();
+    for game in games {
+        for color in game.explain_fit(bag) {
+            groups.entry(color).or_default().push(game.id);
+        }
+    }
+
+    groups
+}
+
+/// The ids of the games needing at least `at_least` dice of the color.
+pub(crate) fn games_needing(games: &[NumberedGame], color: Color, at_least: u64) -> Vec<u64> {
+    games
+        .iter()
+        .filter(|game| game.get_requirements().get_count(color) >= at_least)
+        .map(|game| game.id)
+        .collect()
+}
+
+/// How many games have their power in each bucket. Buckets are given by their
+/// ascending lower bounds, each spanning up to the next one and the last one
+/// having no upper bound. Powers below the first bound aren't counted.
+pub(crate) fn power_histogram(games: &[NumberedGame], buckets: &[u64]) -> Vec<usize> {
+    let mut histogram = vec![0; buckets.len()];
+    for game in games {
+        let bucket = buckets.partition_point(|&lower_bound| lower_bound <= game.get_power());
+        if let Some(index) = bucket.checked_sub(1) {
+            histogram[index] += 1;
+        }
+    }
+
+    histogram
+}
+
+/// The saturating sum of the games' powers, and the ids of the games whose
+/// power overflows, which are left out of the sum.
+pub(crate) fn powers_with_overflow(games: &[NumberedGame]) -> (u64, Vec<u64>) {
+    let mut sum = 0u64;
+    let mut overflowed = vec![];
+    for game in games {
+        match game.get_requirements().checked_power() {
+            Some(power) => sum = sum.saturating_add(power),
+            None => overflowed.push(game.id),
+        }
+    }
+
+    (sum, overflowed)
+}
+
+/// The sum of the games' powers, which can't overflow as powers are `u64`.
+/// Powers that overflow on their own still count as 0, like in `get_power`.
+pub(crate) fn sum_of_powers_u128(games: &[NumberedGame]) -> u128 {
+    games.iter().map(|game| u128::from(game.get_power())).sum()
+}
+
+/// The smallest bag with which every game is possible.
+pub(crate) fn combined_requirements(games: &[NumberedGame]) -> Bag {
+    let requirements: Vec<Bag> = games.iter().map(NumberedGame::get_requirements).collect();
+    Bag::union_all(&requirements)
+}
+
+/// The dice every game needs at least, as the element-wise minimum of their
+/// requirements. Empty without any game.
+pub(crate) fn common_requirements(games: &[NumberedGame]) -> Bag {
+    games
+        .iter()
+        .map(NumberedGame::get_requirements)
+        .reduce(|common, requirements| common.intersection(&requirements))
+        .unwrap_or_default()
+}
+
+/// The share of games possible with the bag, or 0 without any game.
+pub(crate) fn fraction_possible(games: &[NumberedGame], bag: &Bag) -> f64 {
+    if games.is_empty() {
+        return 0.0;
+    }
+
+    let possible = games
+        .iter()
+        .filter(|game| game.is_possible_for(bag))
+        .count();
+    possible as f64 / games.len() as f64
+}
+
+/// The colors drawn in every game, if all games draw exactly the same colors.
+/// `None` when they don't, or when there are no games.
+pub(crate) fn uniform_colors(games: &[NumberedGame]) -> Option<HashSet<Color>> {
+    let mut colors = games.iter().map(|game| {
+        let requirements = game.get_requirements();
+        Color::ALL
+            .into_iter()
+            .filter(|&color| requirements.get_count(color) > 0)
+            .collect::<HashSet<Color>>()
+    });
+
+    let first = colors.next()?;
+    colors.all(|other| other == first).then_some(first)
+}
+
+/// A difference between two parsed inputs, for a given game id.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum GameDiff {
+    OnlyInLeft(u64),
+    OnlyInRight(u64),
+    RequirementsMismatch { id: u64, left: Bag, right: Bag },
+}
+
+/// Compares the games of both inputs by id, ordered by id. Games with the same
+/// id differ when their requirements do.
+pub(crate) fn diff_games(left: &[NumberedGame], right: &[NumberedGame]) -> Vec<GameDiff> {
+    let requirements = |games: &[NumberedGame]| -> BTreeMap<u64, Bag> {
+        games
+            .iter()
+            .map(|game| (game.id, game.get_requirements()))
+            .collect()
+    };
+    let left = requirements(left);
+    let mut right = requirements(right);
+
+    let mut diffs = vec![];
+    for (id, left) in left {
+        match right.remove(&id) {
+            None => diffs.push(GameDiff::OnlyInLeft(id)),
+            Some(right) if right != left => {
+                diffs.push(GameDiff::RequirementsMismatch { id, left, right })
+            }
+            Some(_) => {}
+        }
+    }
+    diffs.extend(right.into_keys().map(GameDiff::OnlyInRight));
+    diffs.sort_by_key(|diff| match diff {
+        GameDiff::OnlyInLeft(id)
+        | GameDiff::OnlyInRight(id)
+        | GameDiff::RequirementsMismatch { id, .. } => *id,
+    });
+
+    diffs
+}
+
+/// Computes the sum of the games' powers and their combined requirements one
+/// game at a time, without keeping the games around.
+#[derive(Default)]
+pub(crate) struct RequirementsAccumulator {
+    power_sum: u64,
+    combined_requirements: Bag,
+}
+
+impl RequirementsAccumulator {
+    pub(crate) fn new() -> Self {
+        RequirementsAccumulator::default()
+    }
+
+    pub(crate) fn push(&mut self, game: &NumberedGame) {
+        let requirements = game.get_requirements();
+        self.power_sum = self.power_sum.saturating_add(requirements.get_power());
+        self.combined_requirements.merge_max_in_place(&requirements);
+    }
+
+    pub(crate) fn finish(self) -> (u64, Bag) {
+        (self.power_sum, self.combined_requirements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::super::dice::{Bag, BagBuilder, Color};
+    use super::super::input::{enumerate_games, parse_input, NumberedGame};
+    use super::{
+        combined_requirements, common_requirements, diff_games, distinct_ids, find_id_gaps,
+        fraction_possible, games_needing, group_by_color_violation, max_power_game,
+        partition_by_possibility, power_histogram, powers_with_overflow, sum_of_powers_u128,
+        uniform_colors, GameDiff, RequirementsAccumulator,
+    };
+
+    fn example() -> Vec<NumberedGame> {
+        parse_input(
+            r#"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+            Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+            Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+            Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_the_game_with_the_highest_power() {
+        let games = example();
+        let game = max_power_game(&games).unwrap();
+
+        assert_eq!(3, game.id);
+        assert_eq!(1560, game.get_power());
+    }
+
+    #[test]
+    fn breaks_ties_with_the_lowest_id() {
+        let games = parse_input(
+            r#"
+            Game 7: 2 red, 3 blue
+            Game 2: 3 red, 2 blue
+            Game 5: 1 red
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(2, max_power_game(&games).unwrap().id);
+    }
+
+    #[test]
+    fn finds_nothing_without_games() {
+        assert_eq!(None, max_power_game(&[]));
+    }
+
+    #[test]
+    fn partitions_games_by_possibility() {
+        let games = example();
+        let (possible, impossible) = partition_by_possibility(&games, &Bag::standard());
+
+        let ids = |games: Vec<&NumberedGame>| games.iter().map(|game| game.id).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 5], ids(possible));
+        assert_eq!(vec![3, 4], ids(impossible));
+    }
+
+    #[test]
+    fn finds_no_gaps_in_sequential_ids() {
+        assert_eq!(Vec::<u64>::new(), find_id_gaps(&example()));
+        assert_eq!(Vec::<u64>::new(), find_id_gaps(&[]));
+    }
+
+    #[test]
+    fn finds_missing_ids() {
+        let games = parse_input(
+            r#"
+            Game 3: 1 red
+            Game 7: 1 red
+            Game 4: 1 red
+            Game 6: 1 red
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(vec![5], find_id_gaps(&games));
+    }
+
+    #[test]
+    fn lists_distinct_ids_in_order() {
+        let games = parse_input(
+            r#"
+            Game 3: 1 red
+            Game 7: 1 red
+            Game 3: 2 blue
+            Game 1: 1 red
+            Game 7: 1 green
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(vec![1, 3, 7], distinct_ids(&games));
+        assert_eq!(vec![1, 2, 3, 4, 5], distinct_ids(&example()));
+        assert_eq!(Vec::<u64>::new(), distinct_ids(&[]));
+    }
+
+    #[test]
+    fn groups_impossible_games_by_limiting_color() {
+        assert_eq!(
+            HashMap::from([(Color::Red, vec![3, 4]), (Color::Blue, vec![4])]),
+            group_by_color_violation(&example(), &Bag::standard())
+        );
+    }
+
+    #[test]
+    fn reports_games_whose_power_overflows() {
+        assert_eq!((2286, vec![]), powers_with_overflow(&example()));
+
+        let games = parse_input(
+            r#"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 7: 4294967296 red, 4294967296 green, 2 blue
+            "#,
+        )
+        .unwrap();
+        assert_eq!((48, vec![7]), powers_with_overflow(&games));
+    }
+
+    #[test]
+    fn sums_powers_past_u64() {
+        assert_eq!(2286, sum_of_powers_u128(&example()));
+
+        let games = parse_input(
+            r#"
+            Game 1: 4294967295 red, 4294967295 green, 1 blue
+            Game 2: 4294967295 red, 4294967295 green, 1 blue
+            Game 3: 1 red, 2 green, 3 blue
+            "#,
+        )
+        .unwrap();
+        let power = u128::from(u32::MAX) * u128::from(u32::MAX);
+
+        assert!(games.iter().all(|game| game.get_power() > 0));
+        assert_eq!(2 * power + 6, sum_of_powers_u128(&games));
+        assert!(sum_of_powers_u128(&games) > u128::from(u64::MAX));
+    }
+
+    #[test]
+    fn combines_requirements_of_all_games() {
+        let want = BagBuilder::new()
+            .with_dice(Color::Red, 20)
+            .with_dice(Color::Green, 13)
+            .with_dice(Color::Blue, 15)
+            .build();
+
+        assert_eq!(want, combined_requirements(&example()));
+        assert_eq!(Bag::default(), combined_requirements(&[]));
+    }
+
+    #[test]
+    fn finds_the_requirements_common_to_all_games() {
+        assert_eq!(Bag::from_array([1, 2, 2]), common_requirements(&example()));
+        assert_eq!(
+            Bag::from_array([4, 2, 6]),
+            common_requirements(&example()[..1])
+        );
+        assert_eq!(Bag::default(), common_requirements(&[]));
+    }
+
+    #[test]
+    fn computes_the_fraction_of_possible_games() {
+        assert_eq!(0.6, fraction_possible(&example(), &Bag::standard()));
+        assert_eq!(0.0, fraction_possible(&example(), &Bag::default()));
+        assert_eq!(0.0, fraction_possible(&[], &Bag::standard()));
+    }
+
+    #[test]
+    fn accumulates_requirements_while_streaming() {
+        let input = r#"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+            Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+            Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+            Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+        "#;
+
+        let mut accumulator = RequirementsAccumulator::new();
+        for (_, game) in enumerate_games(input) {
+            accumulator.push(&game.unwrap());
+        }
+        let (power_sum, combined) = accumulator.finish();
+
+        assert_eq!(2286, power_sum);
+        assert_eq!(combined_requirements(&example()), combined);
+    }
+
+    #[test]
+    fn detects_games_drawing_the_same_colors() {
+        // Every sample game draws all three colors, even if not in every set.
+        assert_eq!(Some(HashSet::from(Color::ALL)), uniform_colors(&example()));
+        assert_eq!(None, uniform_colors(&[]));
+
+        let games = parse_input(
+            r#"
+            Game 1: 3 blue, 4 red; 2 green
+            Game 2: 1 green, 2 red, 3 blue
+            "#,
+        )
+        .unwrap();
+        assert_eq!(Some(HashSet::from(Color::ALL)), uniform_colors(&games));
+
+        let games = parse_input(
+            r#"
+            Game 1: 3 blue, 4 red
+            Game 2: 1 green, 2 red, 3 blue
+            "#,
+        )
+        .unwrap();
+        assert_eq!(None, uniform_colors(&games));
+    }
+
+    #[test]
+    fn diffs_parsed_inputs_by_id() {
+        assert_eq!(Vec::<GameDiff>::new(), diff_games(&example(), &example()));
+
+        let other = parse_input(
+            r#"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+            Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 16 blue, 14 red
+            Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+            Game 6: 1 red
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                GameDiff::OnlyInLeft(2),
+                GameDiff::RequirementsMismatch {
+                    id: 4,
+                    left: Bag::from_array([14, 3, 15]),
+                    right: Bag::from_array([14, 3, 16]),
+                },
+                GameDiff::OnlyInRight(6),
+            ],
+            diff_games(&example(), &other)
+        );
+    }
+
+    #[test]
+    fn finds_games_needing_many_dice_of_a_color() {
+        assert_eq!(vec![3], games_needing(&example(), Color::Red, 15));
+        assert_eq!(vec![3, 4], games_needing(&example(), Color::Red, 14));
+        assert_eq!(
+            vec![1, 2, 3, 4, 5],
+            games_needing(&example(), Color::Red, 0)
+        );
+        assert_eq!(
+            Vec::<u64>::new(),
+            games_needing(&example(), Color::Blue, 16)
+        );
+    }
+
+    #[test]
+    fn counts_games_per_power_bucket() {
+        // The sample's powers are 48, 12, 1560, 630 and 36.
+        assert_eq!(vec![3, 1, 1], power_histogram(&example(), &[0, 50, 1000]));
+        assert_eq!(vec![1, 1, 2], power_histogram(&example(), &[36, 48, 630]));
+        assert_eq!(vec![1, 0], power_histogram(&example(), &[1000, 2000]));
+        assert_eq!(Vec::<usize>::new(), power_histogram(&example(), &[]));
+    }
+}
@@ -0,0 +1,36 @@
+//! Entry points for the benchmarks, which can only reach the public API.
+//! Only built with the `bench` feature.
+
+use super::dice::Bag;
+use super::input::{self, NumberedGame};
+
+/// Parsed games, opaque outside of the crate.
+pub struct Games(Vec<NumberedGame>);
+
+pub fn parse_input(input: &str) -> Games {
+    Games(input::parse_input(input).expect("benchmark input is valid"))
+}
+
+/// How many games fit in the standard bag, checking colors in map order.
+pub fn count_possible(games: &Games) -> usize {
+    count_possible_with(games, Bag::can_contain)
+}
+
+/// How many games fit in the standard bag, checking colors in a fixed order.
+pub fn count_possible_ordered(games: &Games) -> usize {
+    count_possible_with(games, Bag::can_contain_ordered)
+}
+
+fn count_possible_with(games: &Games, can_contain: fn(&Bag, &Bag) -> bool) -> usize {
+    let bag = Bag::standard();
+    games
+        .0
+        .iter()
+        .filter(|game| {
+            game.game()
+                .sets()
+                .iter()
+                .all(|set| can_contain(&bag, set.as_bag()))
+        })
+        .count()
+}
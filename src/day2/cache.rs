@@ -0,0 +1,76 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use super::input::{Error, NumberedGame, Parser};
+
+type Games = Arc<Vec<NumberedGame>>;
+
+/// Remembers the games parsed from each input, so that parsing the same input
+/// again returns the same shared games instead of parsing it anew.
+/// Inputs are kept alongside their games, to tell hash collisions apart.
+#[derive(Default)]
+pub(crate) struct CachedParser {
+    parser: Parser,
+    cache: Mutex<HashMap<u64, (String, Games)>>,
+}
+
+impl CachedParser {
+    pub(crate) fn new(parser: Parser) -> Self {
+        CachedParser {
+            parser,
+            cache: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn parse_input(&self, input: &str) -> Result<Games, Error> {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        let key = hasher.finish();
+
+        let mut cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some((cached_input, games)) = cache.get(&key) {
+            if cached_input == input {
+                return Ok(Arc::clone(games));
+            }
+        }
+
+        let games = Arc::new(self.parser.parse_input(input)?);
+        cache.insert(key, (input.to_string(), Arc::clone(&games)));
+
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::super::input::{Error, Parser};
+    use super::CachedParser;
+
+    const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
+
+    #[test]
+    fn returns_cached_games_for_the_same_input() {
+        let parser = CachedParser::default();
+
+        let first = parser.parse_input(INPUT).unwrap();
+        let same_input = String::from(INPUT);
+        let second = parser.parse_input(&same_input).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let other = parser.parse_input("Game 2: 1 red").unwrap();
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(2, other[0].id);
+    }
+
+    #[test]
+    fn does_not_cache_errors() {
+        let parser = CachedParser::new(Parser::new().reject_zero_counts(true));
+
+        assert_eq!(Err(Error::ZeroCount), parser.parse_input("Game 1: 0 red"));
+        assert_eq!(Err(Error::ZeroCount), parser.parse_input("Game 1: 0 red"));
+    }
+}
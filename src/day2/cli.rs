@@ -0,0 +1,264 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use super::aggregate::{aggregate, PossibleIdSum, PowerSum};
+use super::dice::Bag;
+use super::input;
+use super::report::{Columns, Csv, Json, ReportFormat, Table};
+use super::solve;
+
+const INPUT: &str = include_str!("input.txt");
+
+const USAGE: &str =
+    "usage: advent-2023 [--check] [--part 1|2] [--verbose] [--format table|csv|json] [INPUT]";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliError {
+    UnknownArgument(String),
+    MissingValue(&'static str),
+    UnknownPart(String),
+    UnknownFormat(String),
+    CheckWithOutput,
+    Input(String),
+    InvalidLines(Vec<String>),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownArgument(arg) => write!(f, "unknown argument `{arg}`\n{USAGE}"),
+            CliError::MissingValue(flag) => write!(f, "missing value for `{flag}`\n{USAGE}"),
+            CliError::UnknownPart(part) => {
+                write!(f, "unknown part `{part}`, expected one of: 1, 2")
+            }
+            CliError::UnknownFormat(format) => {
+                write!(
+                    f,
+                    "unknown format `{format}`, expected one of: table, csv, json"
+                )
+            }
+            CliError::CheckWithOutput => {
+                write!(
+                    f,
+                    "`--check` can't be combined with `--part`, `--verbose` or `--format`\n{USAGE}"
+                )
+            }
+            CliError::Input(err) => write!(f, "invalid input: {err}"),
+            CliError::InvalidLines(errors) => write!(f, "invalid input:\n{}", errors.join("\n")),
+        }
+    }
+}
+
+impl From<input::Error> for CliError {
+    fn from(err: input::Error) -> Self {
+        CliError::Input(err.to_string())
+    }
+}
+
+impl From<input::LocatedError> for CliError {
+    fn from(err: input::LocatedError) -> Self {
+        CliError::Input(err.to_string())
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    check: bool,
+    part: Option<u8>,
+    format: Option<String>,
+    path: Option<PathBuf>,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, CliError> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--part" => {
+                let part = args.next().ok_or(CliError::MissingValue("--part"))?;
+                match part.as_str() {
+                    "1" => options.part = Some(1),
+                    "2" => options.part = Some(2),
+                    _ => return Err(CliError::UnknownPart(part)),
+                }
+            }
+            "--check" => options.check = true,
+            "--verbose" => {
+                options.format.get_or_insert_with(|| "table".to_string());
+            }
+            "--format" => {
+                let format = args.next().ok_or(CliError::MissingValue("--format"))?;
+                options.format = Some(format);
+            }
+            _ if arg.starts_with("--") => return Err(CliError::UnknownArgument(arg)),
+            _ => options.path = Some(PathBuf::from(arg)),
+        }
+    }
+    if options.check && (options.part.is_some() || options.format.is_some()) {
+        return Err(CliError::CheckWithOutput);
+    }
+
+    Ok(options)
+}
+
+fn report_format(name: &str) -> Result<Box<dyn ReportFormat>, CliError> {
+    match name {
+        "table" => Ok(Box::new(Table)),
+        "csv" => Ok(Box::new(Csv)),
+        "json" => Ok(Box::new(Json)),
+        _ => Err(CliError::UnknownFormat(name.to_string())),
+    }
+}
+
+/// Runs the command line with the given arguments (without the program name),
+/// returning what should be printed.
+///
+/// Prints the answers by default, or only the one for `--part`. `--verbose`
+/// prints a per-game report instead, in the `--format` of choice (a table by
+/// default), limited to the columns `--part` needs if given. `--check` only
+/// validates the input, listing every invalid line, so it can't be combined
+/// with the other flags. Reads the bundled puzzle input unless given a path.
+pub fn run(args: impl IntoIterator<Item = String>) -> Result<String, CliError> {
+    let options = parse_args(args)?;
+    let format = options.format.as_deref().map(report_format).transpose()?;
+    let input = match &options.path {
+        Some(path) => solve::load_input(path)?,
+        None => INPUT.to_string(),
+    };
+
+    if options.check {
+        let games = input::parse_input_collect(&input).map_err(|errors| {
+            CliError::InvalidLines(errors.iter().map(ToString::to_string).collect())
+        })?;
+        return Ok(format!("ok: {} games\n", games.len()));
+    }
+
+    let games = input::parse_input_located(&input)?;
+    if let Some(format) = format {
+        let columns = match options.part {
+            Some(1) => Columns::Part1,
+            Some(_) => Columns::Part2,
+            None => Columns::All,
+        };
+        return Ok(format.render_columns(&games, &Bag::standard(), columns));
+    }
+
+    let part1 = aggregate(&games, PossibleIdSum(Bag::standard()));
+    let part2 = aggregate(&games, PowerSum);
+    Ok(match options.part {
+        Some(1) => format!("{part1}\n"),
+        Some(_) => format!("{part2}\n"),
+        None => format!("Part 1: {part1}\nPart 2: {part2}\n"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, CliError};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn prints_both_answers_by_default() {
+        assert_eq!(
+            Ok("Part 1: 1931\nPart 2: 83105\n".to_string()),
+            run(args(&[]))
+        );
+    }
+
+    #[test]
+    fn prints_a_single_part() {
+        assert_eq!(Ok("1931\n".to_string()), run(args(&["--part", "1"])));
+        assert_eq!(Ok("83105\n".to_string()), run(args(&["--part", "2"])));
+    }
+
+    #[test]
+    fn prints_a_report_in_the_chosen_format() {
+        let table = run(args(&["--verbose"])).unwrap();
+        assert!(table.starts_with("  game    red  green   blue"), "{table}");
+
+        let csv = run(args(&["--format", "csv"])).unwrap();
+        assert!(
+            csv.starts_with("id,red,green,blue,power,possible\n"),
+            "{csv}"
+        );
+    }
+
+    #[test]
+    fn checks_the_input_without_solving() {
+        assert_eq!(Ok("ok: 100 games\n".to_string()), run(args(&["--check"])));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert_eq!(
+            Err(CliError::UnknownFormat("xml".to_string())),
+            run(args(&["--format", "xml"]))
+        );
+        assert_eq!(
+            Err(CliError::UnknownPart("3".to_string())),
+            run(args(&["--part", "3"]))
+        );
+        assert_eq!(
+            Err(CliError::MissingValue("--format")),
+            run(args(&["--format"]))
+        );
+        assert_eq!(
+            Err(CliError::UnknownArgument("--fast".to_string())),
+            run(args(&["--fast"]))
+        );
+    }
+
+    #[test]
+    fn prints_a_report_for_a_single_part() {
+        let csv = run(args(&["--part", "1", "--format", "csv"])).unwrap();
+        assert!(csv.starts_with("id,possible\n1,false\n"), "{csv}");
+
+        let table = run(args(&["--verbose", "--part", "2"])).unwrap();
+        assert!(
+            table.starts_with("  game    red  green   blue      power\n"),
+            "{table}"
+        );
+    }
+
+    #[test]
+    fn rejects_a_check_with_other_output() {
+        assert_eq!(
+            Err(CliError::CheckWithOutput),
+            run(args(&["--check", "--part", "1"]))
+        );
+        assert_eq!(
+            Err(CliError::CheckWithOutput),
+            run(args(&["--format", "json", "--check"]))
+        );
+    }
+
+    #[test]
+    fn locates_invalid_input_on_every_path() {
+        let path = std::env::temp_dir().join(format!(
+            "advent-2023-{}-cli-invalid.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, "Game 1: 3 blue\nGame 2: 4 yellow\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let want = Err(CliError::Input(
+            "line 2: unknown color `yellow`: `Game 2: 4 yellow`".to_string(),
+        ));
+        assert_eq!(want, run(args(&[path])));
+        assert_eq!(want, run(args(&["--part", "1", path])));
+        assert_eq!(want, run(args(&["--format", "json", path])));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn explains_unknown_formats() {
+        let err = run(args(&["--format", "xml"])).unwrap_err();
+        assert_eq!(
+            "unknown format `xml`, expected one of: table, csv, json",
+            err.to_string()
+        );
+    }
+}
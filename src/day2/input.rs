@@ -1,42 +1,1304 @@
+use std::fmt;
+
 use super::dice::{Bag, BagBuilder, Color, Game};
 
 #[derive(Debug, PartialEq)]
-pub(crate) struct NumberedGame {
+pub struct NumberedGame {
     pub id: u64,
     game: Game,
 }
 
 impl NumberedGame {
-    pub(crate) fn is_possible_for(&self, bag: &Bag) -> bool {
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn is_possible_for(&self, bag: &Bag) -> bool {
         self.game.fits_in(bag)
     }
 
-    pub(crate) fn get_requirements(&self) -> Bag {
+    pub(crate) fn explain_fit(&self, bag: &Bag) -> Vec<Color> {
+        self.game.explain_fit(bag)
+    }
+
+    /// Whether the game is possible with the puzzle's standard bag.
+    pub(crate) fn is_possible(&self) -> bool {
+        self.is_possible_for(&Bag::standard())
+    }
+
+    /// The colors the game needs more of than the standard bag holds.
+    pub(crate) fn violating_colors(&self) -> Vec<Color> {
+        self.explain_fit(&Bag::standard())
+    }
+
+    pub fn get_requirements(&self) -> Bag {
         self.game.get_requirements()
     }
+
+    pub fn get_power(&self) -> u64 {
+        self.get_requirements().get_power()
+    }
+
+    /// The game's id along with its requirements, all that both parts need.
+    pub(crate) fn id_and_requirements(&self) -> (u64, Bag) {
+        (self.id, self.get_requirements())
+    }
+
+    /// The dice of the standard bag the game never needs, or `None` when the
+    /// game is impossible with it.
+    pub(crate) fn slack(&self) -> Option<Bag> {
+        Bag::standard().checked_sub(&self.get_requirements())
+    }
+
+    /// The smallest number of `reference` bags, emptied into one, the game is
+    /// possible with; 1 when it already fits. `u64::MAX` when the game draws
+    /// a color the reference has no dice of, as no number of bags is enough.
+    pub(crate) fn bags_needed(&self, reference: &Bag) -> u64 {
+        let requirements = self.get_requirements();
+        Color::ALL
+            .into_iter()
+            .map(|color| {
+                let needed = requirements.get_count(color);
+                match (needed, reference.get_count(color)) {
+                    (0, _) => 1,
+                    (_, 0) => u64::MAX,
+                    (_, available) => needed.div_ceil(available),
+                }
+            })
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+impl fmt::Display for NumberedGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}: {}", self.id, self.game)
+    }
+}
+
+/// Whether the line parses to the same game once formatted and parsed again.
+/// Fails for lines that don't parse, or with sets only drawing 0 dice, which
+/// format as nothing.
+pub(crate) fn roundtrip_eq(line: &str) -> bool {
+    let Ok(game) = parse_line(line) else {
+        return false;
+    };
+
+    parse_line(&game.to_string()) == Ok(game)
+}
+
+/// Formats games back into puzzle input, one game per line. Unlike `Display`,
+/// sets only drawing 0 dice are written `0 red`, so the output always parses
+/// back to the same games.
+pub(crate) fn to_input_string(games: &[NumberedGame]) -> String {
+    games
+        .iter()
+        .map(|game| {
+            let sets: Vec<String> = game
+                .game
+                .sets()
+                .iter()
+                .map(|set| {
+                    if set.as_bag() == &Bag::default() {
+                        "0 red".to_string()
+                    } else {
+                        set.to_string()
+                    }
+                })
+                .collect();
+            format!("Game {}: {}\n", game.id, sets.join("; "))
+        })
+        .collect()
+}
+
+#[test]
+fn round_trips_whole_inputs() {
+    let example = r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+    "#;
+    let games = parse_input(example).unwrap();
+
+    let text = to_input_string(&games);
+    assert!(text.starts_with("Game 1: 4 red, 3 blue; "));
+    assert_eq!(5, text.lines().count());
+    assert_eq!(Ok(games), parse_input(&text));
+    assert_eq!("", to_input_string(&[]));
+}
+
+#[test]
+fn round_trips_sets_drawing_no_dice() {
+    let games = parse_input("Game 1: 0 red\nGame 2: 3 red; 0 blue, 0 green").unwrap();
+
+    let text = to_input_string(&games);
+    assert_eq!("Game 1: 0 red\nGame 2: 3 red; 0 red\n", text);
+    assert_eq!(Ok(games), parse_input(&text));
+}
+
+#[test]
+fn round_trips_through_display() {
+    let example = r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+    "#;
+    for line in example
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        assert!(roundtrip_eq(line), "{line}");
+    }
+
+    assert_eq!(
+        "Game 1: 4 red, 3 blue; 2 green",
+        parse_line("Game 1: 3 blue, 4 red; 2 green")
+            .unwrap()
+            .to_string()
+    );
+    assert!(!roundtrip_eq("Game 1: 3 yellow"));
+    assert!(!roundtrip_eq("Game 1: 3 red; 0 blue"));
+}
+
+#[test]
+fn checks_possibility_against_the_standard_bag() {
+    let example = r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+    "#;
+
+    let possible: Vec<u64> = parse_input(example)
+        .unwrap()
+        .iter()
+        .filter(|game| game.is_possible())
+        .map(|game| game.id)
+        .collect();
+    assert_eq!(vec![1, 2, 5], possible);
+}
+
+#[test]
+fn pairs_ids_with_requirements() {
+    let example = r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+    "#;
+
+    let got: Vec<(u64, Bag)> = parse_input(example)
+        .unwrap()
+        .iter()
+        .map(NumberedGame::id_and_requirements)
+        .collect();
+    assert_eq!(
+        vec![
+            (1, Bag::from_array([4, 2, 6])),
+            (2, Bag::from_array([1, 3, 4])),
+            (3, Bag::from_array([20, 13, 6])),
+            (4, Bag::from_array([14, 3, 15])),
+            (5, Bag::from_array([6, 3, 2])),
+        ],
+        got
+    );
+}
+
+#[test]
+fn computes_the_slack_of_possible_games() {
+    let game = parse_line("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+    assert_eq!(Some(Bag::from_array([8, 11, 8])), game.slack());
+
+    let game =
+        parse_line("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red")
+            .unwrap();
+    assert_eq!(None, game.slack());
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub(crate) enum Error {
-    MissingParts,
+#[test]
+fn counts_the_bags_needed_for_a_game() {
+    let game =
+        parse_line("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red")
+            .unwrap();
+    assert_eq!(2, game.bags_needed(&Bag::standard()));
+    assert_eq!(20, game.bags_needed(&Bag::from_array([1, 13, 6])));
+    assert_eq!(u64::MAX, game.bags_needed(&Bag::from_array([12, 13, 0])));
+
+    let game = parse_line("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+    assert_eq!(1, game.bags_needed(&Bag::standard()));
+}
+
+#[test]
+fn lists_colors_exceeding_the_standard_bag() {
+    let game =
+        parse_line("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red");
+    assert_eq!(vec![Color::Red], game.unwrap().violating_colors());
+
+    let game = parse_line("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
+    assert_eq!(Vec::<Color>::new(), game.unwrap().violating_colors());
+}
+
+/// Why a line failed to parse. Public as `day2::ErrorKind`; the public parsing
+/// functions only return some of the kinds, and more may be added.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    MissingColon,
+    MissingSets,
     TooManyParts,
     BadlyFormattedTitle,
     BadlyFormattedDie,
-    UnknownColor,
+    UnknownColor(String),
+    NonIntegerCount(String),
+    ZeroCount,
+    DuplicateColor,
+    TooManySets,
+    GameCountMismatch { expected: usize, found: usize },
+    Io(std::io::ErrorKind),
+}
+
+impl Error {
+    pub(crate) fn with_line(self, line: usize) -> LocatedError {
+        LocatedError {
+            error: self,
+            line,
+            text: None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingColon => write!(f, "missing `:` after the game title"),
+            Error::MissingSets => write!(f, "missing sets after the game title"),
+            Error::TooManyParts => write!(f, "more than one `:` in the game"),
+            Error::BadlyFormattedTitle => write!(f, "badly formatted title, expected `Game <id>`"),
+            Error::BadlyFormattedDie => {
+                write!(f, "badly formatted die, expected `<count> <color>`")
+            }
+            Error::UnknownColor(token) => write!(f, "unknown color `{token}`"),
+            Error::NonIntegerCount(token) => {
+                write!(f, "count `{token}` is not a whole number")
+            }
+            Error::ZeroCount => write!(f, "die with a count of 0"),
+            Error::DuplicateColor => write!(f, "color given more than once"),
+            Error::TooManySets => write!(f, "too many sets in the game"),
+            Error::GameCountMismatch { expected, found } => {
+                write!(f, "expected {expected} games, found {found}")
+            }
+            Error::Io(kind) => write!(f, "could not read the input: {kind}"),
+        }
+    }
+}
+
+/// An error along with the line it was found on, and optionally that line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LocatedError {
+    pub error: Error,
+    pub line: usize,
+    pub text: Option<String>,
+}
+
+impl LocatedError {
+    pub(crate) fn with_text(mut self, text: &str) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+}
+
+impl fmt::Display for LocatedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)?;
+        if let Some(text) = &self.text {
+            write!(f, ": `{text}`")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An error from parsing the puzzle input, pointing at the invalid line when
+/// it is known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: Error,
+    pub line: Option<usize>,
+    pub snippet: Option<String>,
+}
+
+impl From<Error> for ParseError {
+    fn from(kind: Error) -> Self {
+        ParseError {
+            kind,
+            line: None,
+            snippet: None,
+        }
+    }
+}
+
+impl From<LocatedError> for ParseError {
+    fn from(error: LocatedError) -> Self {
+        ParseError {
+            kind: error.error,
+            line: Some(error.line),
+            snippet: error.text,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}: ")?;
+        }
+        write!(f, "{}", self.kind)?;
+        if let Some(snippet) = &self.snippet {
+            write!(f, ": `{snippet}`")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.kind)
+    }
+}
+
+#[test]
+fn locates_parse_errors() {
+    let input = "Game 1: 3 blue, 4 red\n\n  Game 2: 3 yellow  \nGame 3 1 red";
+
+    let error = ParseError::from(parse_input_located(input).unwrap_err());
+    assert_eq!(
+        ParseError {
+            kind: Error::UnknownColor("yellow".to_string()),
+            line: Some(3),
+            snippet: Some("Game 2: 3 yellow".to_string()),
+        },
+        error
+    );
+    assert_eq!(
+        "line 3: unknown color `yellow`: `Game 2: 3 yellow`",
+        error.to_string()
+    );
+
+    let error = ParseError::from(Error::MissingColon);
+    assert_eq!(None, error.line);
+    assert_eq!("missing `:` after the game title", error.to_string());
+}
+
+impl std::error::Error for LocatedError {}
+
+#[test]
+fn formats_located_errors() {
+    let error = Error::UnknownColor("yellow".to_string()).with_line(3);
+    assert_eq!("line 3: unknown color `yellow`", error.to_string());
+
+    let error = Error::BadlyFormattedDie
+        .with_line(12)
+        .with_text("Game 3: two red");
+    assert_eq!(
+        "line 12: badly formatted die, expected `<count> <color>`: `Game 3: two red`",
+        error.to_string()
+    );
+
+    let boxed: Box<dyn std::error::Error> = Box::new(error);
+    assert!(boxed.to_string().contains("line 12"));
 }
 
-pub(crate) fn parse_input(input: &str) -> Result<Vec<NumberedGame>, Error> {
-    let parsed_games = input
+#[test]
+fn converts_errors_to_boxed_errors_with_question_mark() {
+    fn first_id(input: &str) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let games = parse_input(input)?;
+        Ok(games.first().map_or(0, |game| game.id))
+    }
+
+    assert_eq!(7, first_id("Game 7: 1 red").unwrap());
+
+    let error = first_id("Game 7 1 red").unwrap_err();
+    assert_eq!(Error::MissingColon.to_string(), error.to_string());
+    assert_eq!(Some(&Error::MissingColon), error.downcast_ref::<Error>());
+}
+
+/// Parses puzzle input. The default parser accepts the puzzle's format, and
+/// options make it stricter or more lenient.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct Parser {
+    reject_zero_counts: bool,
+    and_separator: bool,
+    max_sets_per_game: Option<usize>,
+    block_mode: bool,
+    bag_header: bool,
+    pipe_separator: bool,
+    game_count_header: bool,
+    digit_separators: bool,
+    semicolons_in_blocks: bool,
+    reject_duplicate_colors: bool,
+    skip_invalid_lines: bool,
+}
+
+impl Parser {
+    pub(crate) fn new() -> Self {
+        Parser::default()
+    }
+
+    /// Rejects anything the puzzle's format wouldn't produce: zero counts and
+    /// colors given twice in a set, on top of what the default parser rejects.
+    pub(crate) fn strict() -> Self {
+        Parser::new()
+            .reject_zero_counts(true)
+            .reject_duplicate_colors(true)
+    }
+
+    /// Accepts `and` between dice and underscores in counts, and skips the
+    /// lines that still fail to parse. Abbreviated colors such as `r` aren't
+    /// supported, so lines using them are skipped too.
+    pub(crate) fn lenient() -> Self {
+        Parser::new()
+            .allow_and_separator(true)
+            .allow_digit_separators(true)
+            .skip_invalid_lines(true)
+    }
+
+    /// Fails with `Error::ZeroCount` on dice such as `0 red`.
+    pub(crate) fn reject_zero_counts(mut self, reject: bool) -> Self {
+        self.reject_zero_counts = reject;
+        self
+    }
+
+    /// Fails with `Error::DuplicateColor` on sets such as `1 red, 2 red`,
+    /// whose counts otherwise add up.
+    pub(crate) fn reject_duplicate_colors(mut self, reject: bool) -> Self {
+        self.reject_duplicate_colors = reject;
+        self
+    }
+
+    /// Leaves the games that fail to parse out of `parse_input`, rather than
+    /// failing.
+    pub(crate) fn skip_invalid_lines(mut self, skip: bool) -> Self {
+        self.skip_invalid_lines = skip;
+        self
+    }
+
+    /// Also separates dice with `and`, as in `3 blue and 4 red`.
+    pub(crate) fn allow_and_separator(mut self, allow: bool) -> Self {
+        self.and_separator = allow;
+        self
+    }
+
+    /// Accepts underscores between the digits of counts, as in `1_000 red`.
+    pub(crate) fn allow_digit_separators(mut self, allow: bool) -> Self {
+        self.digit_separators = allow;
+        self
+    }
+
+    /// Fails with `Error::TooManySets` on games with more sets than `max`.
+    /// Games can have any number of sets by default.
+    pub(crate) fn max_sets_per_game(mut self, max: Option<usize>) -> Self {
+        self.max_sets_per_game = max;
+        self
+    }
+
+    /// Reads games spanning several lines, one set per line, separated by
+    /// blank lines. The first line of each game is its `Game N:` title.
+    pub(crate) fn block_mode(mut self, enabled: bool) -> Self {
+        self.block_mode = enabled;
+        self
+    }
+
+    /// In block mode, also separates sets with `;` within a line, so that
+    /// a game's sets can be split across lines and semicolons alike.
+    pub(crate) fn allow_semicolons_in_blocks(mut self, allow: bool) -> Self {
+        self.semicolons_in_blocks = allow;
+        self
+    }
+
+    /// Also separates games with ` | `, so that several can share a line, as in
+    /// `Game 1: 3 blue | Game 2: 1 red`. Has no effect in block mode.
+    pub(crate) fn allow_pipe_separator(mut self, allow: bool) -> Self {
+        self.pipe_separator = allow;
+        self
+    }
+
+    /// Checks a first line such as `5 games` declaring how many games follow,
+    /// failing with `Error::GameCountMismatch` when there are more or fewer.
+    /// It comes before any `Bag:` header. When off, the line is skipped
+    /// without being checked.
+    pub(crate) fn check_game_count(mut self, enabled: bool) -> Self {
+        self.game_count_header = enabled;
+        self
+    }
+
+    /// Accepts a first line such as `Bag: 12 red, 13 green, 14 blue` declaring
+    /// the bag to play with. See `parse_input_with_bag`.
+    pub(crate) fn bag_header(mut self, enabled: bool) -> Self {
+        self.bag_header = enabled;
+        self
+    }
+
+    /// Parses the games along with the bag declared by the input, or the
+    /// standard bag if it declares none or the header isn't enabled.
+    pub(crate) fn parse_input_with_bag(
+        &self,
+        input: &str,
+    ) -> Result<(Bag, Vec<NumberedGame>), Error> {
+        let after_game_count = match split_game_count_header(input) {
+            Some((_, _, rest)) => rest,
+            None => input,
+        };
+        let bag = match split_bag_header(after_game_count) {
+            Some((dice, _, _)) if self.bag_header => self.parse_set(dice.trim())?,
+            _ => Bag::standard(),
+        };
+
+        Ok((bag, self.parse_input(input)?))
+    }
+
+    pub(crate) fn parse_input(&self, input: &str) -> Result<Vec<NumberedGame>, Error> {
+        let mut games = vec![];
+        for (_, game) in self.enumerate_games(input) {
+            match game {
+                Ok(game) => games.push(game),
+                Err(_) if self.skip_invalid_lines => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if let Some((expected, _, _)) =
+            split_game_count_header(input).filter(|_| self.game_count_header)
+        {
+            if expected != games.len() {
+                return Err(Error::GameCountMismatch {
+                    expected,
+                    found: games.len(),
+                });
+            }
+        }
+
+        Ok(games)
+    }
+
+    /// Parses every game, along with the 1-based line number where it starts.
+    pub(crate) fn enumerate_games<'a>(
+        &self,
+        input: &'a str,
+    ) -> Box<dyn Iterator<Item = (usize, Result<NumberedGame, Error>)> + 'a> {
+        // The count is checked by `parse_input`, if at all.
+        if let Some((_, skipped, rest)) = split_game_count_header(input) {
+            return Box::new(
+                self.enumerate_games_after_count(rest)
+                    .map(move |(line_number, game)| (line_number + skipped, game)),
+            );
+        }
+
+        self.enumerate_games_after_count(input)
+    }
+
+    fn enumerate_games_after_count<'a>(
+        &self,
+        input: &'a str,
+    ) -> Box<dyn Iterator<Item = (usize, Result<NumberedGame, Error>)> + 'a> {
+        if let Some((_, skipped, rest)) = split_bag_header(input).filter(|_| self.bag_header) {
+            return Box::new(
+                self.bag_header(false)
+                    .enumerate_games_after_count(rest)
+                    .map(move |(line_number, game)| (line_number + skipped, game)),
+            );
+        }
+
+        let parser = *self;
+        if self.block_mode {
+            return Box::new(
+                blocks(input)
+                    .map(move |(line_number, block)| (line_number, parser.parse_block(&block))),
+            );
+        }
+
+        // Lines never contain a newline, so splitting on one keeps them whole.
+        let separator = if self.pipe_separator { " | " } else { "\n" };
+        Box::new(
+            input
+                .lines()
+                .enumerate()
+                .flat_map(move |(index, line)| {
+                    line.split(separator)
+                        .map(move |game| (index + 1, game.trim()))
+                })
+                .filter(|&(_, line)| !line.is_empty())
+                .map(move |(line_number, line)| (line_number, parser.parse_line(line))),
+        )
+    }
+
+    fn parse_block(&self, lines: &[&str]) -> Result<NumberedGame, Error> {
+        let (title_line, set_lines) = lines.split_first().ok_or(Error::MissingColon)?;
+        let Some((title, rest)) = title_line.split_once(':') else {
+            return Err(Error::MissingColon);
+        };
+        if rest.contains(':') {
+            return Err(Error::TooManyParts);
+        }
+
+        let id = parse_title(title)?;
+        let lines = std::iter::once(rest)
+            .filter(|rest| !rest.trim().is_empty())
+            .chain(set_lines.iter().copied());
+        // Each line holds one set, unless semicolons split it into several.
+        let sets: Vec<&str> = if self.semicolons_in_blocks {
+            lines.flat_map(|line| line.split(';')).collect()
+        } else {
+            lines.collect()
+        };
+        if sets.is_empty() {
+            return Err(Error::MissingSets);
+        }
+        let game = self.parse_sets(sets)?;
+
+        Ok(NumberedGame { id, game })
+    }
+
+    fn parse_line(&self, line: &str) -> Result<NumberedGame, Error> {
+        let (id, sets) = split_title(line)?;
+        let game = self.parse_game(sets)?;
+
+        Ok(NumberedGame { id, game })
+    }
+
+    fn parse_game(&self, raw: &str) -> Result<Game, Error> {
+        self.parse_sets(raw.trim().split(';'))
+    }
+
+    fn parse_sets<'a>(&self, sets_str: impl IntoIterator<Item = &'a str>) -> Result<Game, Error> {
+        let mut sets = vec![];
+        for set in sets_str {
+            if self.max_sets_per_game == Some(sets.len()) {
+                return Err(Error::TooManySets);
+            }
+
+            let set = self.parse_set(set.trim())?;
+            sets.push(set);
+        }
+
+        Ok(Game::new(sets))
+    }
+
+    /// Dice of the same color add up, so `1 red, 2 red` is 3 red dice,
+    /// saturating at `u64::MAX`. Linear in the number of dice.
+    fn parse_set(&self, raw: &str) -> Result<Bag, Error> {
+        let mut counts = [0u64; 3];
+        let mut seen = [false; 3];
+        for die in self.split_dice(raw) {
+            let (color, count) = self.parse_die_count(die.trim())?;
+            if self.reject_duplicate_colors && seen[color.index()] {
+                return Err(Error::DuplicateColor);
+            }
+            seen[color.index()] = true;
+            counts[color.index()] = counts[color.index()].saturating_add(count);
+        }
+
+        Ok(Bag::from_array(counts))
+    }
+
+    fn split_dice<'a>(&self, raw: &'a str) -> Vec<&'a str> {
+        let dice = raw.split(',');
+        if !self.and_separator {
+            return dice.collect();
+        }
+
+        dice.flat_map(|die| die.split(" and ")).collect()
+    }
+
+    fn parse_die(&self, raw: &str) -> Result<Bag, Error> {
+        let (color, count) = self.parse_die_count(raw)?;
+        Ok(BagBuilder::new().with_dice(color, count).build())
+    }
+
+    fn parse_die_count(&self, raw: &str) -> Result<(Color, u64), Error> {
+        let mut parts = raw.split_whitespace();
+        let Some(raw_count) = parts.next() else {
+            return Err(Error::BadlyFormattedDie);
+        };
+        let Some(color) = parts.next() else {
+            return Err(Error::BadlyFormattedDie);
+        };
+        if parts.next().is_some() {
+            return Err(Error::BadlyFormattedDie);
+        }
+
+        let count = if self.digit_separators && raw_count.contains('_') {
+            raw_count.replace('_', "").parse::<u64>()
+        } else {
+            raw_count.parse::<u64>()
+        };
+        let count = count.map_err(|_| {
+            if raw_count.contains('.') && raw_count.parse::<f64>().is_ok() {
+                Error::NonIntegerCount(raw_count.to_string())
+            } else {
+                Error::BadlyFormattedDie
+            }
+        })?;
+        if self.reject_zero_counts && count == 0 {
+            return Err(Error::ZeroCount);
+        }
+
+        // Color names are ASCII, so no other token can match them, whatever
+        // its case.
+        let color =
+            Color::try_from_ascii(color).map_err(|()| Error::UnknownColor(color.to_string()))?;
+
+        Ok((color, count))
+    }
+}
+
+/// Splits a leading `Bag:` line off the input, returning the dice it declares,
+/// how many lines it took up to and including it, and the rest of the input.
+fn split_bag_header(input: &str) -> Option<(&str, usize, &str)> {
+    split_header(input, |line| line.strip_prefix("Bag:"))
+}
+
+/// Splits a leading `N games` line off the input, returning `N`, how many
+/// lines it took up to and including it, and the rest of the input.
+fn split_game_count_header(input: &str) -> Option<(usize, usize, &str)> {
+    split_header(input, |line| {
+        let (count, word) = line.split_once(' ')?;
+        if !matches!(word.trim(), "game" | "games") {
+            return None;
+        }
+        count.parse().ok()
+    })
+}
+
+/// Applies `parse` to the first non-blank line, returning its result along
+/// with how many lines were consumed and the rest of the input.
+fn split_header<'a, T>(
+    input: &'a str,
+    parse: impl Fn(&'a str) -> Option<T>,
+) -> Option<(T, usize, &'a str)> {
+    let mut offset = 0;
+    for (index, line) in input.split_inclusive('\n').enumerate() {
+        offset += line.len();
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        return Some((parse(line)?, index + 1, &input[offset..]));
+    }
+
+    None
+}
+
+/// Groups consecutive non-blank lines, along with the 1-based line number of
+/// the first line of each group.
+fn blocks(input: &str) -> impl Iterator<Item = (usize, Vec<&str>)> + '_ {
+    let mut lines = input.lines().map(str::trim).enumerate().peekable();
+    std::iter::from_fn(move || {
+        while lines.next_if(|(_, line)| line.is_empty()).is_some() {}
+
+        let (index, first_line) = lines.next()?;
+        let mut block = vec![first_line];
+        while let Some((_, line)) = lines.next_if(|(_, line)| !line.is_empty()) {
+            block.push(line);
+        }
+
+        Some((index + 1, block))
+    })
+}
+
+#[test]
+fn separates_sets_with_semicolons_in_blocks_when_configured() {
+    let input = r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue
+        2 green
+
+        Game 2:
+        1 blue, 2 green; 3 green, 4 blue, 1 red
+        1 green, 1 blue
+    "#;
+    let want = parse_input(
+        r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+    "#,
+    );
+
+    let parser = Parser::new()
+        .block_mode(true)
+        .allow_semicolons_in_blocks(true);
+    assert_eq!(want, parser.parse_input(input));
+    assert_eq!(3, parser.parse_input(input).unwrap()[0].game().sets().len());
+    assert_eq!(
+        Err(Error::BadlyFormattedDie),
+        Parser::new().block_mode(true).parse_input(input)
+    );
+}
+
+#[test]
+fn parses_games_in_blocks_when_configured() {
+    let input = r#"
+        Game 1:
+        3 blue, 4 red
+        1 red, 2 green, 6 blue
+        2 green
+
+        Game 2: 1 blue, 2 green
+        3 green, 4 blue, 1 red
+
+
+        Game 3:
+        8 green, 6 blue, 20 red
+    "#;
+    let want = parse_input(
+        r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red
+        Game 3: 8 green, 6 blue, 20 red
+    "#,
+    );
+
+    let parser = Parser::new().block_mode(true);
+    assert_eq!(want, parser.parse_input(input));
+
+    let line_numbers: Vec<usize> = parser
+        .enumerate_games(input)
+        .map(|(line_number, _)| line_number)
+        .collect();
+    assert_eq!(vec![2, 7, 11], line_numbers);
+
+    assert_eq!(Err(Error::MissingSets), Parser::new().parse_input(input));
+    assert_eq!(
+        Err(Error::MissingSets),
+        parser.parse_input("Game 1:\n\nGame 2:\n1 red")
+    );
+}
+
+#[test]
+fn separates_games_with_pipes_when_configured() {
+    let input = "Game 1: 3 blue, 4 red; 2 green | Game 2: 1 blue, 2 green\nGame 3: 1 red";
+    let parser = Parser::new().allow_pipe_separator(true);
+
+    assert_eq!(
+        parse_input("Game 1: 3 blue, 4 red; 2 green\nGame 2: 1 blue, 2 green\nGame 3: 1 red"),
+        parser.parse_input(input)
+    );
+
+    let line_numbers: Vec<usize> = parser
+        .enumerate_games(input)
+        .map(|(line_number, _)| line_number)
+        .collect();
+    assert_eq!(vec![1, 1, 2], line_numbers);
+
+    assert_eq!(Err(Error::TooManyParts), Parser::new().parse_input(input));
+}
+
+#[test]
+fn rejects_fractional_counts() {
+    let parser = Parser::new();
+
+    assert_eq!(
+        Err(Error::NonIntegerCount("3.5".to_string())),
+        parser.parse_set("3.5 red")
+    );
+    assert_eq!(
+        "count `3.5` is not a whole number",
+        Error::NonIntegerCount("3.5".to_string()).to_string()
+    );
+    assert_eq!(Err(Error::BadlyFormattedDie), parser.parse_set("3.x red"));
+    assert_eq!(Err(Error::BadlyFormattedDie), parser.parse_set("-3 red"));
+}
+
+#[test]
+fn accepts_digit_separators_when_configured() {
+    let parser = Parser::new().allow_digit_separators(true);
+
+    assert_eq!(
+        Ok(Bag::from_array([1000, 0, 0])),
+        parser.parse_set("1_000 red")
+    );
+    assert_eq!(
+        Ok(Bag::from_array([0, 1_234_567, 2])),
+        parser.parse_set("1_234_567 green, 2 blue")
+    );
+    assert_eq!(Err(Error::BadlyFormattedDie), parser.parse_set("_ red"));
+    assert_eq!(
+        Err(Error::BadlyFormattedDie),
+        Parser::new().parse_set("1_000 red")
+    );
+}
+
+#[test]
+fn rejects_duplicate_colors_when_configured() {
+    let set = "1 red, 2 blue, 2 red";
+
+    assert_eq!(Ok(Bag::from_array([3, 0, 2])), Parser::new().parse_set(set));
+    assert_eq!(
+        Err(Error::DuplicateColor),
+        Parser::new().reject_duplicate_colors(true).parse_set(set)
+    );
+}
+
+#[test]
+fn applies_strictness_presets() {
+    let input = r#"
+        Game 1: 3 blue, 4 red
+        Game 2: 1 red and 2 green
+        Game 3: 1_000 blue
+        Game 4: 0 red, 1 blue
+        Game 5: 1 red, 2 red
+        Game 6: 3 yellow
+    "#;
+    let lines: Vec<&str> = input.lines().map(str::trim).collect();
+    let game = |n: usize| lines[n];
+
+    assert_eq!(
+        Err(Error::BadlyFormattedDie),
+        Parser::default().parse_input(input)
+    );
+    for n in [1, 4, 5] {
+        assert!(Parser::default().parse_input(game(n)).is_ok(), "{n}");
+    }
+
+    let strict = Parser::strict();
+    assert!(strict.parse_input(game(1)).is_ok());
+    assert_eq!(Err(Error::ZeroCount), strict.parse_input(game(4)));
+    assert_eq!(Err(Error::DuplicateColor), strict.parse_input(game(5)));
+    assert_eq!(
+        Err(Error::UnknownColor("yellow".to_string())),
+        strict.parse_input(game(6))
+    );
+
+    let games = Parser::lenient().parse_input(input).unwrap();
+    let ids: Vec<u64> = games.iter().map(|game| game.id).collect();
+    assert_eq!(vec![1, 2, 3, 4, 5], ids);
+    assert_eq!(Bag::from_array([1, 2, 0]), games[1].get_requirements());
+    assert_eq!(Bag::from_array([0, 0, 1000]), games[2].get_requirements());
+    assert_eq!(
+        Ok(vec![]),
+        Parser::lenient().parse_input("Game 7: 3 r, 2 b")
+    );
+}
+
+#[test]
+fn rejects_zero_counts_when_configured() {
+    let line = "Game 1: 3 red; 0 blue";
+
+    assert!(Parser::new().parse_input(line).is_ok());
+    assert_eq!(
+        Err(Error::ZeroCount),
+        Parser::new().reject_zero_counts(true).parse_input(line)
+    );
+}
+
+#[test]
+fn separates_dice_with_and_when_configured() {
+    let set = "3 blue and 4 red";
+    let want = BagBuilder::new()
+        .with_dice(Color::Blue, 3)
+        .with_dice(Color::Red, 4)
+        .build();
+
+    assert_eq!(Err(Error::BadlyFormattedDie), Parser::new().parse_set(set));
+    assert_eq!(
+        Ok(want),
+        Parser::new().allow_and_separator(true).parse_set(set)
+    );
+    assert_eq!(
+        Ok(BagBuilder::new()
+            .with_dice(Color::Blue, 3)
+            .with_dice(Color::Red, 4)
+            .with_dice(Color::Green, 1)
+            .build()),
+        Parser::new()
+            .allow_and_separator(true)
+            .parse_set("3 blue, 4 red and 1 green")
+    );
+}
+
+#[test]
+fn limits_sets_per_game_when_configured() {
+    let line = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
+
+    assert!(Parser::new().parse_input(line).is_ok());
+    assert!(Parser::new()
+        .max_sets_per_game(Some(3))
+        .parse_input(line)
+        .is_ok());
+    assert_eq!(
+        Err(Error::TooManySets),
+        Parser::new().max_sets_per_game(Some(2)).parse_input(line)
+    );
+}
+
+#[test]
+fn parses_non_ascii_input_like_ascii_input() {
+    // An em space, which `trim` and `split_whitespace` handle like a space.
+    let input = "Game 1: 3 blue,\u{2003}4 RED; 2 Green\n\u{2003}\n";
+    assert!(!input.is_ascii());
+    assert_eq!(
+        parse_input("Game 1: 3 blue, 4 RED; 2 Green"),
+        parse_input(input)
+    );
+
+    assert_eq!(
+        Err(Error::UnknownColor("réd".to_string())),
+        parse_input("Game 1: 3 réd")
+    );
+    assert_eq!(
+        Err(Error::UnknownColor("red;".to_string())),
+        Parser::new().parse_die("3 red;")
+    );
+}
+
+#[test]
+fn ignores_the_declared_game_count_by_default() {
+    let input = "3 games\nGame 1: 3 blue, 4 red\nGame 2: 1 blue, 2 green";
+
+    assert_eq!(
+        parse_input("Game 1: 3 blue, 4 red\nGame 2: 1 blue, 2 green"),
+        Parser::new().parse_input(input)
+    );
+    assert_eq!(
+        vec![2, 3],
+        Parser::new()
+            .enumerate_games(input)
+            .map(|(line_number, _)| line_number)
+            .collect::<Vec<usize>>()
+    );
+    assert_eq!(
+        Err(Error::MissingColon),
+        Parser::new().parse_input("three games\nGame 1: 3 blue")
+    );
+}
+
+#[test]
+fn checks_the_declared_game_count_when_configured() {
+    let input = r#"
+        3 games
+        Game 1: 3 blue, 4 red
+        Game 2: 1 blue, 2 green
+    "#;
+    let parser = Parser::new().check_game_count(true);
+
+    assert_eq!(
+        Err(Error::GameCountMismatch {
+            expected: 3,
+            found: 2
+        }),
+        parser.parse_input(input)
+    );
+    assert_eq!(
+        parse_input("Game 1: 3 blue, 4 red\nGame 2: 1 blue, 2 green"),
+        parser.parse_input(&input.replace("3 games", "2 games"))
+    );
+    assert_eq!(
+        Ok(1),
+        parser
+            .parse_input("Game 1: 3 blue")
+            .map(|games| games.len())
+    );
+
+    let (bag, games) = parser
+        .bag_header(true)
+        .parse_input_with_bag("1 game\nBag: 1 blue\nGame 1: 1 blue")
+        .unwrap();
+    assert_eq!(Bag::from_array([0, 0, 1]), bag);
+    assert_eq!(1, games.len());
+}
+
+#[test]
+fn parses_a_bag_header_when_configured() {
+    let input = r#"
+        Bag: 20 red, 13 green, 14 blue
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+    "#;
+    let parser = Parser::new().bag_header(true);
+
+    let (bag, games) = parser.parse_input_with_bag(input).unwrap();
+    assert_eq!(Bag::from_array([20, 13, 14]), bag);
+    assert_eq!(
+        vec![1, 3],
+        games.iter().map(|game| game.id).collect::<Vec<u64>>()
+    );
+    assert!(games.iter().all(|game| game.is_possible_for(&bag)));
+
+    let line_numbers: Vec<usize> = parser
+        .enumerate_games(input)
+        .map(|(line_number, _)| line_number)
+        .collect();
+    assert_eq!(vec![3, 4], line_numbers);
+
+    assert_eq!(
+        Err(Error::BadlyFormattedTitle),
+        Parser::new().parse_input_with_bag(input)
+    );
+    assert_eq!(
+        Bag::standard(),
+        parser.parse_input_with_bag("Game 1: 3 blue").unwrap().0
+    );
+}
+
+/// Trims every line and collapses runs of spaces and tabs into one space.
+/// Clean input comes out unchanged.
+pub(crate) fn normalize_input(input: &str) -> String {
+    let mut normalized: Vec<String> = input
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .collect();
+    if input.ends_with('\n') {
+        normalized.push(String::new());
+    }
+
+    normalized.join("\n")
+}
+
+#[test]
+fn normalizes_whitespace() {
+    let messy =
+        "  Game 1:   3 blue,\t4 red;  1 red, 2   green, 6 blue ;2 green  \n\t\nGame 2: 1 red";
+    let clean = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue ;2 green\n\nGame 2: 1 red";
+
+    assert_eq!(clean, normalize_input(messy));
+    assert_eq!(clean, normalize_input(clean));
+    assert_eq!("Game 2: 1 red\n", normalize_input("Game 2: 1 red\n"));
+    assert_eq!(parse_input(messy), parse_input(&normalize_input(messy)));
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<NumberedGame>, Error> {
+    Parser::default().parse_input(input)
+}
+
+pub(crate) fn enumerate_games(
+    input: &str,
+) -> impl Iterator<Item = (usize, Result<NumberedGame, Error>)> + '_ {
+    Parser::default().enumerate_games(input)
+}
+
+/// Parses only the game on the `n`th non-empty line, counting from 0, without
+/// parsing the other lines. `None` when there are fewer lines.
+pub(crate) fn parse_nth(input: &str, n: usize) -> Result<Option<NumberedGame>, Error> {
+    input
         .lines()
         .map(str::trim)
-        .filter(|&line| !line.is_empty())
-        .map(parse_line);
+        .filter(|line| !line.is_empty())
+        .nth(n)
+        .map(parse_line)
+        .transpose()
+}
+
+#[test]
+fn parses_only_the_nth_game() {
+    let example = r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+        Game 6: 2 yellow
+    "#;
+
+    let game = parse_nth(example, 3).unwrap().unwrap();
+    assert_eq!(4, game.id);
+    assert_eq!(Bag::from_array([14, 3, 15]), game.get_requirements());
+
+    assert_eq!(Ok(None), parse_nth(example, 6));
+    assert_eq!(
+        Err(Error::UnknownColor("yellow".to_string())),
+        parse_nth(example, 5)
+    );
+}
+
+/// Like `parse_input`, but the error comes with its line.
+pub(crate) fn parse_input_located(input: &str) -> Result<Vec<NumberedGame>, LocatedError> {
+    parse_games_located(input).collect()
+}
+
+/// Parses every game, locating errors at their line.
+fn parse_games_located(
+    input: &str,
+) -> impl Iterator<Item = Result<NumberedGame, LocatedError>> + '_ {
+    let lines: Vec<&str> = input.lines().collect();
+    enumerate_games(input).map(move |(line_number, game)| {
+        game.map_err(|error| {
+            error
+                .with_line(line_number)
+                .with_text(lines[line_number - 1].trim())
+        })
+    })
+}
+
+/// Parses every game, or fails with all the errors found, each along with its
+/// line, rather than only the first one.
+pub(crate) fn parse_input_collect(input: &str) -> Result<Vec<NumberedGame>, Vec<LocatedError>> {
     let mut games = vec![];
-    for game in parsed_games {
-        games.push(game?);
+    let mut errors = vec![];
+    for game in parse_games_located(input) {
+        match game {
+            Ok(game) => games.push(game),
+            Err(error) => errors.push(error),
+        }
     }
 
-    Ok(games)
+    if errors.is_empty() {
+        Ok(games)
+    } else {
+        Err(errors)
+    }
+}
+
+#[test]
+fn collects_all_errors() {
+    let input = "Game 1: 3 blue, 4 red
+        Game 2 1 blue
+        Game 3: 8 yellow";
+
+    let errors = parse_input_collect(input).unwrap_err();
+    assert_eq!(
+        vec![
+            Error::MissingColon.with_line(2).with_text("Game 2 1 blue"),
+            Error::UnknownColor("yellow".to_string())
+                .with_line(3)
+                .with_text("Game 3: 8 yellow"),
+        ],
+        errors
+    );
+
+    let valid = "Game 1: 3 blue, 4 red\nGame 2: 1 blue";
+    assert_eq!(Ok(parse_input(valid).unwrap()), parse_input_collect(valid));
+}
+
+#[test]
+fn enumerates_games_with_line_numbers() {
+    let input = "Game 1: 3 blue, 4 red
+
+        Game 2: 1 blue, 2 green
+
+        Game 3: 8 yellow
+        Game 4: 1 green, 3 red, 6 blue";
+
+    let got: Vec<(usize, Result<u64, Error>)> = enumerate_games(input)
+        .map(|(line_number, game)| (line_number, game.map(|game| game.id)))
+        .collect();
+
+    assert_eq!(
+        vec![
+            (1, Ok(1)),
+            (3, Ok(2)),
+            (5, Err(Error::UnknownColor("yellow".to_string()))),
+            (6, Ok(4)),
+        ],
+        got
+    );
 }
 
 #[test]
@@ -90,22 +1352,8 @@ fn can_parse_example_input() {
     assert_eq!(Game::new(sets), fourth_game.game)
 }
 
-fn parse_line(line: &str) -> Result<NumberedGame, Error> {
-    let mut parts = line.trim().split(':');
-    let Some(title) = parts.next() else {
-        return Err(Error::MissingParts);
-    };
-    let Some(sets) = parts.next() else {
-        return Err(Error::MissingParts);
-    };
-    if parts.next().is_some() {
-        return Err(Error::TooManyParts);
-    }
-
-    let id = parse_title(title)?;
-    let game = parse_game(sets)?;
-
-    Ok(NumberedGame { id, game })
+pub(crate) fn parse_line(line: &str) -> Result<NumberedGame, Error> {
+    Parser::default().parse_line(line)
 }
 
 #[cfg(test)]
@@ -137,15 +1385,24 @@ mod parse_line_tests {
     }
 
     #[test]
-    fn detects_missing_parts() {
+    fn detects_missing_colon() {
         let line = "";
-        assert_eq!(Err(Error::MissingParts), parse_line(line));
+        assert_eq!(Err(Error::MissingColon), parse_line(line));
 
         let line = "Game 1";
-        assert_eq!(Err(Error::MissingParts), parse_line(line));
+        assert_eq!(Err(Error::MissingColon), parse_line(line));
 
         let line = "3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
-        assert_eq!(Err(Error::MissingParts), parse_line(line));
+        assert_eq!(Err(Error::MissingColon), parse_line(line));
+    }
+
+    #[test]
+    fn detects_missing_sets() {
+        let line = "Game 1:";
+        assert_eq!(Err(Error::MissingSets), parse_line(line));
+
+        let line = "Game 1:    ";
+        assert_eq!(Err(Error::MissingSets), parse_line(line));
     }
 
     #[test]
@@ -155,6 +1412,38 @@ mod parse_line_tests {
     }
 }
 
+/// Splits a line into its game id and the trimmed sets that follow the title.
+pub(crate) fn split_title(line: &str) -> Result<(u64, &str), Error> {
+    let mut parts = line.trim().split(':');
+    let title = parts.next().unwrap_or_default();
+    let Some(sets) = parts.next() else {
+        return Err(Error::MissingColon);
+    };
+    if parts.next().is_some() {
+        return Err(Error::TooManyParts);
+    }
+    let sets = sets.trim();
+    if sets.is_empty() {
+        return Err(Error::MissingSets);
+    }
+
+    Ok((parse_title(title)?, sets))
+}
+
+#[test]
+fn splits_the_title_from_the_sets() {
+    assert_eq!(
+        Ok((1, "3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green")),
+        split_title("  Game 1:   3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green ")
+    );
+    assert_eq!(Err(Error::MissingColon), split_title("Game 1"));
+    assert_eq!(Err(Error::MissingSets), split_title("Game 1: "));
+    assert_eq!(
+        Err(Error::BadlyFormattedTitle),
+        split_title("Game one: 3 blue")
+    );
+}
+
 fn parse_title(raw: &str) -> Result<u64, Error> {
     let raw = raw.trim();
 
@@ -217,22 +1506,7 @@ mod parse_title_tests {
 }
 
 fn parse_die(raw: &str) -> Result<Bag, Error> {
-    let mut parts = raw.split_whitespace();
-    let Some(count) = parts.next() else {
-        return Err(Error::BadlyFormattedDie);
-    };
-    let Some(color) = parts.next() else {
-        return Err(Error::BadlyFormattedDie);
-    };
-    if parts.next().is_some() {
-        return Err(Error::BadlyFormattedDie);
-    }
-
-    let count = count.parse::<u64>().map_err(|_| Error::BadlyFormattedDie)?;
-
-    let color = Color::try_from_str(color).map_err(|()| Error::UnknownColor)?;
-
-    Ok(BagBuilder::new().with_dice(color, count).build())
+    Parser::default().parse_die(raw)
 }
 
 #[cfg(test)]
@@ -243,7 +1517,24 @@ mod parse_die_tests {
     #[test]
     fn detects_unknown_color() {
         let set = "2 yellow";
-        assert_eq!(Err(Error::UnknownColor), parse_die(set));
+        assert_eq!(
+            Err(Error::UnknownColor("yellow".to_string())),
+            parse_die(set)
+        );
+    }
+
+    #[test]
+    fn rejects_color_tokens_with_punctuation() {
+        for (die, token) in [
+            ("2 red;", "red;"),
+            ("2 re-d", "re-d"),
+            ("2 .blue", ".blue"),
+            ("2 green!", "green!"),
+            ("2 gr33n", "gr33n"),
+        ] {
+            assert_eq!(Err(Error::UnknownColor(token.to_string())), parse_die(die));
+        }
+        assert!(parse_die("2 ReD").is_ok());
     }
 
     #[test]
@@ -289,27 +1580,56 @@ mod parse_die_tests {
     }
 }
 
-fn parse_set(raw: &str) -> Result<Bag, Error> {
-    let dice = raw.split(',');
+pub(crate) fn parse_set(raw: &str) -> Result<Bag, Error> {
+    Parser::default().parse_set(raw)
+}
 
-    let mut bag = BagBuilder::new();
+impl Game {
+    /// Builds a game from one string per set, like `"3 blue, 4 red"`.
+    pub(crate) fn from_set_strings(sets: &[&str]) -> Result<Game, Error> {
+        let mut parsed = vec![];
+        for set in sets {
+            parsed.push(parse_set(set.trim())?);
+        }
 
-    for die in dice {
-        let die = parse_die(die.trim())?;
-        bag = bag.with_bag(&die);
+        Ok(Game::new(parsed))
     }
+}
 
-    Ok(bag.build())
+#[test]
+fn builds_a_game_from_set_strings() {
+    let game = Game::from_set_strings(&["3 blue, 4 red", "1 red, 2 green, 6 blue", "2 green"]);
+    let sets = vec![
+        BagBuilder::new()
+            .with_dice(Color::Blue, 3)
+            .with_dice(Color::Red, 4)
+            .build(),
+        BagBuilder::new()
+            .with_dice(Color::Red, 1)
+            .with_dice(Color::Green, 2)
+            .with_dice(Color::Blue, 6)
+            .build(),
+        BagBuilder::new().with_dice(Color::Green, 2).build(),
+    ];
+    assert_eq!(Ok(Game::new(sets)), game);
+
+    assert_eq!(
+        Err(Error::UnknownColor("yellow".to_string())),
+        Game::from_set_strings(&["3 blue", "2 yellow"])
+    );
 }
 
 #[cfg(test)]
 mod parse_set_tests {
-    use super::{parse_set, BagBuilder, Color, Error};
+    use super::{parse_set, Bag, BagBuilder, Color, Error};
 
     #[test]
     fn detects_invalid_die() {
         let set = "2 yellow";
-        assert_eq!(Err(Error::UnknownColor), parse_set(set));
+        assert_eq!(
+            Err(Error::UnknownColor("yellow".to_string())),
+            parse_set(set)
+        );
 
         let set = "two red";
         assert_eq!(Err(Error::BadlyFormattedDie), parse_set(set));
@@ -360,17 +1680,28 @@ mod parse_set_tests {
             parse_set(set)
         );
     }
-}
 
-fn parse_game(raw: &str) -> Result<Game, Error> {
-    let sets_str = raw.trim().split(';');
-    let mut sets = vec![];
-    for set in sets_str {
-        let set = parse_set(set.trim())?;
-        sets.push(set);
+    #[test]
+    fn adds_up_dice_of_the_same_color() {
+        let set = "1 red, 2 blue, 2 red, 0 green";
+        assert_eq!(Ok(Bag::from_array([3, 0, 2])), parse_set(set));
+
+        let set = format!("{} red, 1 red", u64::MAX);
+        assert_eq!(Ok(Bag::from_array([u64::MAX, 0, 0])), parse_set(&set));
     }
 
-    Ok(Game::new(sets))
+    #[test]
+    fn parses_very_long_sets() {
+        let set = ["1 red", "2 green", "3 blue", "4 red"]
+            .repeat(2_500)
+            .join(", ");
+
+        assert_eq!(Ok(Bag::from_array([12_500, 5_000, 7_500])), parse_set(&set));
+    }
+}
+
+fn parse_game(raw: &str) -> Result<Game, Error> {
+    Parser::default().parse_game(raw)
 }
 
 #[cfg(test)]
@@ -1,9 +1,37 @@
 #![allow(dead_code)]
 
 //! --- Day 2: Cube Conundrum ---
-//! https://adventofcode.com/2023/day/2#part2
+//! <https://adventofcode.com/2023/day/2#part2>
 
+mod aggregate;
+mod analysis;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
+#[cfg(feature = "cache")]
+mod cache;
+mod cli;
 mod dice;
 mod input;
 mod part1;
 mod part2;
+mod report;
+mod solve;
+
+pub use cli::{run, CliError};
+pub use dice::{Bag, BagBuilder, Color, Draw, Game};
+pub use input::{Error as ErrorKind, NumberedGame, ParseError};
+pub use part1::solve as solve_part1;
+pub use part1::solve_with_bag_str as solve_part1_with_bag_str;
+pub use part2::solve as solve_part2;
+
+/// Parses the puzzle input, one game per line. Fails at the first invalid
+/// line, which the error points at.
+pub fn parse_input(input: &str) -> Result<Vec<NumberedGame>, ParseError> {
+    Ok(input::parse_input_located(input)?)
+}
+
+/// The answer to part 1 for the bundled puzzle input.
+pub const DAY2_PART1_ANSWER: u64 = 1931;
+/// The answer to part 2 for the bundled puzzle input.
+pub const DAY2_PART2_ANSWER: u64 = 83105;
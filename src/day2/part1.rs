@@ -45,8 +45,84 @@ fn get_possible_game_numbers(games: Vec<input::NumberedGame>, bag: &dice::Bag) -
         .collect()
 }
 
+/// Saturates at `u64::MAX` rather than overflowing.
 fn calculate_sum(game_numbers: Vec<u64>) -> u64 {
-    game_numbers.iter().sum()
+    game_numbers
+        .iter()
+        .fold(0, |sum, &id| sum.saturating_add(id))
+}
+
+/// Sums the ids of the possible games by checking each game's requirements,
+/// instead of each of its sets, against the bag.
+fn sum_possible_ids_via_requirements(games: &[input::NumberedGame], bag: &dice::Bag) -> u64 {
+    games
+        .iter()
+        .filter(|game| bag.is_superset_of(&game.get_requirements()))
+        .fold(0, |sum, game| sum.saturating_add(game.id))
+}
+
+/// Parses the input, keeping only the games possible with the bag.
+pub(crate) fn parse_and_filter(
+    input: &str,
+    bag: &dice::Bag,
+) -> Result<Vec<input::NumberedGame>, input::Error> {
+    let mut games = input::parse_input(input)?;
+    games.retain(|game| game.is_possible_for(bag));
+
+    Ok(games)
+}
+
+pub fn solve(input: &str) -> Result<u64, input::ParseError> {
+    let games = input::parse_input_located(input)?;
+    let possible_game_numbers = games
+        .into_iter()
+        .filter(input::NumberedGame::is_possible)
+        .map(|game| game.id)
+        .collect();
+
+    Ok(calculate_sum(possible_game_numbers))
+}
+
+/// Like `solve`, but with the bag given like a set of the input, as in
+/// `10 red, 10 green, 10 blue`.
+pub fn solve_with_bag_str(input: &str, bag: &str) -> Result<u64, input::ParseError> {
+    let bag = input::parse_set(bag)?;
+    let games = input::parse_input_located(input)?;
+
+    Ok(sum_possible_ids_via_requirements(&games, &bag))
+}
+
+#[test]
+fn solves_with_a_bag_given_as_a_string() {
+    let input = r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+    "#;
+
+    assert_eq!(
+        solve(input),
+        solve_with_bag_str(input, "12 red, 13 green, 14 blue")
+    );
+    assert_eq!(
+        Ok(1 + 2),
+        solve_with_bag_str(input, "5 red, 5 green, 6 blue")
+    );
+    assert_eq!(
+        Err(input::Error::UnknownColor("purple".to_string()).into()),
+        solve_with_bag_str(input, "10 purple")
+    );
+}
+
+#[test]
+fn reports_where_the_input_is_invalid() {
+    let err = solve("Game 1: 1 red\nGame 2: 1 red, 2 yellow").unwrap_err();
+
+    assert_eq!(input::Error::UnknownColor("yellow".to_string()), err.kind);
+    assert_eq!(Some(2), err.line);
+    assert_eq!(Some("Game 2: 1 red, 2 yellow"), err.snippet.as_deref());
 }
 
 #[test]
@@ -71,6 +147,44 @@ fn can_reproduce_the_example() {
     assert_eq!(8, result);
 }
 
+#[test]
+fn parses_only_the_possible_games() {
+    let input = r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+    "#;
+
+    let games = parse_and_filter(input, &dice::Bag::standard()).unwrap();
+    let ids: Vec<u64> = games.iter().map(|game| game.id).collect();
+    assert_eq!(vec![1, 2, 5], ids);
+
+    assert!(parse_and_filter("Game 1: 3 yellow", &dice::Bag::standard()).is_err());
+}
+
+#[test]
+fn requirements_and_sets_agree_on_possible_games() {
+    for input in [
+        INPUT,
+        r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+    "#,
+    ] {
+        let games = input::parse_input(input).unwrap();
+        let bag = dice::Bag::standard();
+
+        let via_requirements = sum_possible_ids_via_requirements(&games, &bag);
+        let via_sets = calculate_sum(get_possible_game_numbers(games, &bag));
+        assert_eq!(via_sets, via_requirements);
+    }
+}
+
 #[test]
 fn can_calculate_the_part1_result() {
     let games = input::parse_input(INPUT).unwrap();
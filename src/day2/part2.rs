@@ -29,16 +29,30 @@ use super::{dice, input};
 const INPUT: &str = include_str!("input.txt");
 
 fn get_minimum_requirements(games: Vec<input::NumberedGame>) -> dice::Bag {
-    let mut bag = dice::BagBuilder::new();
-    for game in games {
-        let requirements = game.get_requirements();
-        bag = bag.with_bag(&requirements);
-    }
-    bag.build()
+    games
+        .iter()
+        .fold(dice::BagBuilder::new(), |builder, game| {
+            builder.with_bag_max(&game.get_requirements())
+        })
+        .build()
 }
 
+/// Saturates at `u64::MAX` rather than overflowing.
 fn calculate_result(powers: Vec<u64>) -> u64 {
-    powers.iter().sum()
+    powers
+        .iter()
+        .fold(0, |sum, &power| sum.saturating_add(power))
+}
+
+pub fn solve(input: &str) -> Result<u64, input::ParseError> {
+    let games = input::parse_input_located(input)?;
+    let powers = games
+        .iter()
+        .map(input::NumberedGame::get_requirements)
+        .map(|requirement| requirement.get_power())
+        .collect();
+
+    Ok(calculate_result(powers))
 }
 
 #[test]
@@ -0,0 +1,349 @@
+use std::io::{self, Write};
+
+use super::dice::Bag;
+use super::input::NumberedGame;
+
+/// Renders, for each game, its minimum requirements, their power and whether
+/// the game is possible with the given bag.
+pub(crate) trait ReportFormat {
+    fn render(&self, games: &[NumberedGame], bag: &Bag) -> String {
+        self.render_columns(games, bag, Columns::All)
+    }
+
+    /// Renders only the given columns, in the same format.
+    fn render_columns(&self, games: &[NumberedGame], bag: &Bag, columns: Columns) -> String;
+}
+
+pub(crate) struct Json;
+pub(crate) struct Csv;
+pub(crate) struct Table;
+
+/// Which columns a report shows: every one, or only those a part needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Columns {
+    All,
+    /// The id and whether the game is possible.
+    Part1,
+    /// The id, the requirements and their power.
+    Part2,
+}
+
+impl Columns {
+    fn list(self) -> &'static [Column] {
+        use Column::*;
+
+        match self {
+            Columns::All => &[Id, Red, Green, Blue, Power, Possible],
+            Columns::Part1 => &[Id, Possible],
+            Columns::Part2 => &[Id, Red, Green, Blue, Power],
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Column {
+    Id,
+    Red,
+    Green,
+    Blue,
+    Power,
+    Possible,
+}
+
+impl Column {
+    /// The CSV header and JSON key.
+    fn name(self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Red => "red",
+            Column::Green => "green",
+            Column::Blue => "blue",
+            Column::Power => "power",
+            Column::Possible => "possible",
+        }
+    }
+
+    fn value(self, row: &Row) -> String {
+        let [red, green, blue] = row.counts;
+        match self {
+            Column::Id => row.id.to_string(),
+            Column::Red => red.to_string(),
+            Column::Green => green.to_string(),
+            Column::Blue => blue.to_string(),
+            Column::Power => row.power.to_string(),
+            Column::Possible => row.possible.to_string(),
+        }
+    }
+
+    /// Pads `text` to the column's width, preceded by its separator.
+    fn table_cell(self, text: &str) -> String {
+        match self {
+            Column::Id => format!("{text:>6}"),
+            Column::Red | Column::Green | Column::Blue => format!(" {text:>6}"),
+            Column::Power => format!(" {text:>10}"),
+            Column::Possible => format!("  {text}"),
+        }
+    }
+}
+
+struct Row {
+    id: u64,
+    counts: [u64; 3],
+    power: u64,
+    possible: bool,
+}
+
+impl Row {
+    fn new(game: &NumberedGame, bag: &Bag) -> Self {
+        let requirements = game.get_requirements();
+        Row {
+            id: game.id,
+            counts: requirements.to_array(),
+            power: requirements.get_power(),
+            possible: game.is_possible_for(bag),
+        }
+    }
+}
+
+fn rows<'a>(games: &'a [NumberedGame], bag: &'a Bag) -> impl Iterator<Item = Row> + 'a {
+    games.iter().map(|game| Row::new(game, bag))
+}
+
+impl ReportFormat for Json {
+    fn render_columns(&self, games: &[NumberedGame], bag: &Bag, columns: Columns) -> String {
+        let objects: Vec<String> = rows(games, bag)
+            .map(|row| {
+                let fields: Vec<String> = columns
+                    .list()
+                    .iter()
+                    .map(|column| format!(r#""{}": {}"#, column.name(), column.value(&row)))
+                    .collect();
+                format!("  {{{}}}", fields.join(", "))
+            })
+            .collect();
+
+        if objects.is_empty() {
+            return "[]\n".to_string();
+        }
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+impl ReportFormat for Csv {
+    fn render_columns(&self, games: &[NumberedGame], bag: &Bag, columns: Columns) -> String {
+        let mut csv = vec![];
+        write_report_csv_columns(&mut csv, games, bag, columns)
+            .expect("writing to a Vec can't fail");
+        String::from_utf8(csv).expect("the report is valid UTF-8")
+    }
+}
+
+/// Writes the CSV report one row at a time, without building it in memory.
+pub(crate) fn write_report_csv<W: Write>(
+    w: &mut W,
+    games: &[NumberedGame],
+    bag: &Bag,
+) -> io::Result<()> {
+    write_report_csv_columns(w, games, bag, Columns::All)
+}
+
+/// Same as `write_report_csv`, with only the given columns.
+pub(crate) fn write_report_csv_columns<W: Write>(
+    w: &mut W,
+    games: &[NumberedGame],
+    bag: &Bag,
+    columns: Columns,
+) -> io::Result<()> {
+    let header: Vec<&str> = columns.list().iter().map(|column| column.name()).collect();
+    writeln!(w, "{}", header.join(","))?;
+    for row in rows(games, bag) {
+        let values: Vec<String> = columns
+            .list()
+            .iter()
+            .map(|column| column.value(&row))
+            .collect();
+        writeln!(w, "{}", values.join(","))?;
+    }
+
+    Ok(())
+}
+
+impl ReportFormat for Table {
+    fn render_columns(&self, games: &[NumberedGame], bag: &Bag, columns: Columns) -> String {
+        let mut table = String::new();
+        for column in columns.list() {
+            let header = match column {
+                Column::Id => "game",
+                _ => column.name(),
+            };
+            table.push_str(&column.table_cell(header));
+        }
+        table.push('\n');
+
+        for row in rows(games, bag) {
+            for column in columns.list() {
+                let value = match column {
+                    Column::Possible if row.possible => "yes".to_string(),
+                    Column::Possible => "no".to_string(),
+                    _ => column.value(&row),
+                };
+                table.push_str(&column.table_cell(&value));
+            }
+            table.push('\n');
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dice::Bag;
+    use super::super::input::parse_input;
+    use super::{Columns, Csv, Json, ReportFormat, Table};
+
+    #[test]
+    fn renders_every_format_through_the_trait() {
+        let games = parse_input(
+            r#"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+            "#,
+        )
+        .unwrap();
+        let formats: Vec<(Box<dyn ReportFormat>, [&str; 2])> = vec![
+            (
+                Box::new(Json),
+                [
+                    r#"{"id": 1, "red": 4, "green": 2, "blue": 6, "power": 48, "possible": true}"#,
+                    r#"{"id": 3, "red": 20, "green": 13, "blue": 6, "power": 1560, "possible": false}"#,
+                ],
+            ),
+            (Box::new(Csv), ["1,4,2,6,48,true", "3,20,13,6,1560,false"]),
+            (
+                Box::new(Table),
+                [
+                    "     1      4      2      6         48  yes",
+                    "     3     20     13      6       1560  no",
+                ],
+            ),
+        ];
+
+        for (format, expected_rows) in formats {
+            let rendered = format.render(&games, &Bag::standard());
+            for expected_row in expected_rows {
+                assert!(rendered.contains(expected_row), "{rendered}");
+            }
+        }
+    }
+
+    #[test]
+    fn renders_only_the_columns_a_part_needs() {
+        let games = parse_input("Game 3: 8 green, 6 blue, 20 red").unwrap();
+        let bag = Bag::standard();
+
+        assert_eq!(
+            "[\n  {\"id\": 3, \"possible\": false}\n]\n",
+            Json.render_columns(&games, &bag, Columns::Part1)
+        );
+        assert_eq!(
+            "id,red,green,blue,power\n3,20,8,6,960\n",
+            Csv.render_columns(&games, &bag, Columns::Part2)
+        );
+        assert_eq!(
+            "  game  possible\n     3  no\n",
+            Table.render_columns(&games, &bag, Columns::Part1)
+        );
+        assert_eq!(
+            "  game    red  green   blue      power\n     3     20      8      6        960\n",
+            Table.render_columns(&games, &bag, Columns::Part2)
+        );
+    }
+
+    #[test]
+    fn renders_no_games() {
+        assert_eq!("[]\n", Json.render(&[], &Bag::standard()));
+        assert_eq!(
+            "id,red,green,blue,power,possible\n",
+            Csv.render(&[], &Bag::standard())
+        );
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::super::dice::Bag;
+    use super::super::input::{parse_input, NumberedGame};
+    use super::{write_report_csv, Csv, Json, ReportFormat, Table};
+
+    const TABLE: &str = "  game    red  green   blue      power  possible
+     1      4      2      6         48  yes
+     2      1      3      4         12  yes
+     3     20     13      6       1560  no
+     4     14      3     15        630  no
+     5      6      3      2         36  yes
+";
+
+    const CSV: &str = "id,red,green,blue,power,possible
+1,4,2,6,48,true
+2,1,3,4,12,true
+3,20,13,6,1560,false
+4,14,3,15,630,false
+5,6,3,2,36,true
+";
+
+    const JSON: &str = r#"[
+  {"id": 1, "red": 4, "green": 2, "blue": 6, "power": 48, "possible": true},
+  {"id": 2, "red": 1, "green": 3, "blue": 4, "power": 12, "possible": true},
+  {"id": 3, "red": 20, "green": 13, "blue": 6, "power": 1560, "possible": false},
+  {"id": 4, "red": 14, "green": 3, "blue": 15, "power": 630, "possible": false},
+  {"id": 5, "red": 6, "green": 3, "blue": 2, "power": 36, "possible": true}
+]
+"#;
+
+    fn example() -> Vec<NumberedGame> {
+        parse_input(
+            r#"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+            Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+            Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+            Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn table_matches_snapshot() {
+        assert_eq!(TABLE, Table.render(&example(), &Bag::standard()));
+    }
+
+    #[test]
+    fn csv_matches_snapshot() {
+        assert_eq!(CSV, Csv.render(&example(), &Bag::standard()));
+    }
+
+    #[test]
+    fn writes_csv_matching_snapshot() {
+        let mut csv = vec![];
+        write_report_csv(&mut csv, &example(), &Bag::standard()).unwrap();
+
+        assert_eq!(CSV.as_bytes(), csv);
+    }
+
+    #[test]
+    fn json_matches_snapshot() {
+        assert_eq!(JSON, Json.render(&example(), &Bag::standard()));
+    }
+
+    #[test]
+    fn renders_identically_every_time() {
+        let games = example();
+        for format in [&Table as &dyn ReportFormat, &Csv, &Json] {
+            let first = format.render(&games, &Bag::standard());
+            for _ in 0..10 {
+                assert_eq!(first, format.render(&example(), &Bag::standard()));
+            }
+        }
+    }
+}
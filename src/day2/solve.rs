@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::input::{self, Error};
+use super::{part1, part2};
+
+pub(crate) fn load_input(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|err| Error::Io(err.kind()))
+}
+
+pub(crate) fn solve_both(input: &str) -> Result<(u64, u64), Error> {
+    let kind = |err: input::ParseError| err.kind;
+    Ok((
+        part1::solve(input).map_err(kind)?,
+        part2::solve(input).map_err(kind)?,
+    ))
+}
+
+/// Solves both parts one line at a time, as lines arrive, without keeping the
+/// input or the games around. Stops at the first line that fails to parse.
+/// Both sums saturate at `u64::MAX`.
+pub(crate) fn solve_streaming<I: Iterator<Item = String>>(lines: I) -> Result<(u64, u64), Error> {
+    let (mut part1, mut part2) = (0u64, 0u64);
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let game = input::parse_line(&line)?;
+        if game.is_possible() {
+            part1 = part1.saturating_add(game.id);
+        }
+        part2 = part2.saturating_add(game.get_power());
+    }
+
+    Ok((part1, part2))
+}
+
+/// Like `solve_both`, but parses the lines and solves each game in parallel,
+/// summing the partial results. When several lines fail to parse, any one of
+/// their errors may be returned.
+#[cfg(feature = "rayon")]
+pub(crate) fn solve_both_par(input: &str) -> Result<(u64, u64), Error> {
+    use rayon::prelude::*;
+
+    input
+        .par_lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let game = input::parse_line(line)?;
+            let part1 = if game.is_possible() { game.id } else { 0 };
+            Ok((part1, game.get_power()))
+        })
+        .try_reduce(
+            || (0, 0),
+            |a, b| Ok((a.0.saturating_add(b.0), a.1.saturating_add(b.1))),
+        )
+}
+
+/// Solves both parts for each file, stopping at the first file that fails.
+pub(crate) fn solve_many(paths: &[PathBuf]) -> Result<Vec<(PathBuf, u64, u64)>, Error> {
+    let mut results = vec![];
+    for path in paths {
+        let input = load_input(path)?;
+        let (part1, part2) = solve_both(&input)?;
+        results.push((path.clone(), part1, part2));
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::super::{DAY2_PART1_ANSWER, DAY2_PART2_ANSWER};
+    use super::{solve_both, solve_many, solve_streaming, Error};
+
+    const EXAMPLE: &str = r#"
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+    "#;
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("advent-2023-{}-{name}", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn solves_both_parts_of_the_example() {
+        assert_eq!(Ok((8, 2286)), solve_both(EXAMPLE));
+    }
+
+    #[test]
+    fn solves_both_parts_of_the_puzzle_input() {
+        assert_eq!(
+            Ok((DAY2_PART1_ANSWER, DAY2_PART2_ANSWER)),
+            solve_both(include_str!("input.txt"))
+        );
+    }
+
+    #[test]
+    fn solves_lines_as_they_arrive() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for line in EXAMPLE.lines() {
+            sender.send(line.to_string()).unwrap();
+        }
+        drop(sender);
+
+        assert_eq!(Ok((8, 2286)), solve_streaming(receiver.into_iter()));
+        assert_eq!(
+            Err(Error::MissingColon),
+            solve_streaming(["Game 1: 1 red", "Game 2"].map(String::from).into_iter())
+        );
+    }
+
+    #[test]
+    fn saturates_streamed_sums() {
+        let large_id = format!("Game {}: 1 red", u64::MAX);
+        let large_power = "Game 1: 4294967295 red, 4294967295 green, 1 blue".to_string();
+        let lines = [large_id.clone(), large_id, large_power.clone(), large_power];
+
+        assert_eq!(Ok((u64::MAX, u64::MAX)), solve_streaming(lines.into_iter()));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn solves_in_parallel_like_sequentially() {
+        use super::solve_both_par;
+
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |max: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % max + 1
+        };
+        let input: String = (1..=10_000)
+            .map(|id| {
+                let sets: Vec<String> = (0..next(5))
+                    .map(|_| format!("{} red, {} green, {} blue", next(20), next(20), next(20)))
+                    .collect();
+                format!("Game {id}: {}\n", sets.join("; "))
+            })
+            .collect();
+
+        assert_eq!(solve_both(&input), solve_both_par(&input));
+        assert_eq!(solve_both(EXAMPLE), solve_both_par(EXAMPLE));
+        assert_eq!(
+            Ok((DAY2_PART1_ANSWER, DAY2_PART2_ANSWER)),
+            solve_both_par(include_str!("input.txt"))
+        );
+        assert_eq!(
+            Err(Error::MissingColon),
+            solve_both_par("Game 1: 1 red\nGame 2")
+        );
+
+        let large = format!(
+            "Game {}: 1 red\nGame 2: 1 red\n{}",
+            u64::MAX,
+            "Game 3: 4294967295 red, 4294967295 green, 1 blue\n".repeat(2)
+        );
+        assert_eq!(Ok((u64::MAX, u64::MAX)), solve_both(&large));
+        assert_eq!(solve_both(&large), solve_both_par(&large));
+    }
+
+    #[test]
+    fn solves_many_files() {
+        let example = temp_file("example.txt", EXAMPLE);
+        let subset = temp_file(
+            "subset.txt",
+            r#"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+            "#,
+        );
+
+        let results = solve_many(&[example.clone(), subset.clone()]);
+        fs::remove_file(&example).unwrap();
+        fs::remove_file(&subset).unwrap();
+
+        assert_eq!(
+            Ok(vec![(example, 8, 2286), (subset, 1, 48 + 1560)]),
+            results
+        );
+    }
+
+    #[test]
+    fn fails_on_missing_file() {
+        let missing = std::env::temp_dir().join("advent-2023-this-file-does-not-exist.txt");
+        assert_eq!(
+            Err(Error::Io(std::io::ErrorKind::NotFound)),
+            solve_many(&[missing])
+        );
+    }
+}
@@ -1 +1,26 @@
-mod day2;
+//! Solutions to Advent of Code 2023.
+//!
+//! ```
+//! use advent_2023::day2::{parse_input, solve_part1, solve_part2, Bag};
+//!
+//! let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+//! Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
+//!
+//! let games = parse_input(input).unwrap();
+//! assert!(games[0].is_possible_for(&Bag::standard()));
+//! assert!(!games[1].is_possible_for(&Bag::standard()));
+//! assert_eq!(3, games[0].game().sets().len());
+//!
+//! assert_eq!(Ok(1), solve_part1(input));
+//! assert_eq!(Ok(48 + 1560), solve_part2(input));
+//!
+//! let err = parse_input("Game 1: 3 blue\nGame 2 4 red").unwrap_err();
+//! assert_eq!(Some(2), err.line);
+//! ```
+
+pub mod day2;
+
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub use day2::bench;
+pub use day2::{DAY2_PART1_ANSWER, DAY2_PART2_ANSWER};
@@ -0,0 +1,14 @@
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match advent_2023::day2::run(std::env::args().skip(1)) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::process::Command;
+
+fn advent(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_advent-2023"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn prints_games_as_json() {
+    let output = advent(&["--format", "json"]);
+    assert!(output.status.success());
+
+    let games: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let games = games.as_array().unwrap();
+    assert_eq!(100, games.len());
+
+    let possible_ids: u64 = games
+        .iter()
+        .filter(|game| game["possible"].as_bool().unwrap())
+        .map(|game| game["id"].as_u64().unwrap())
+        .sum();
+    assert_eq!(1931, possible_ids);
+
+    let powers: u64 = games
+        .iter()
+        .map(|game| game["power"].as_u64().unwrap())
+        .sum();
+    assert_eq!(83105, powers);
+}
+
+#[test]
+fn prints_part1_games_as_json() {
+    let output = advent(&["--part", "1", "--format", "json"]);
+    assert!(output.status.success());
+
+    let games: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let games = games.as_array().unwrap();
+    assert_eq!(100, games.len());
+    for game in games {
+        let keys: Vec<&str> = game
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(vec!["id", "possible"], keys);
+    }
+
+    let possible_ids: u64 = games
+        .iter()
+        .filter(|game| game["possible"].as_bool().unwrap())
+        .map(|game| game["id"].as_u64().unwrap())
+        .sum();
+    assert_eq!(1931, possible_ids);
+}
+
+#[test]
+fn prints_part2_games_as_csv() {
+    let output = advent(&["--part", "2", "--format", "csv"]);
+    assert!(output.status.success());
+
+    let csv = String::from_utf8(output.stdout).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(Some("id,red,green,blue,power"), lines.next());
+
+    let powers: u64 = lines
+        .map(|line| line.rsplit(',').next().unwrap().parse::<u64>().unwrap())
+        .sum();
+    assert_eq!(83105, powers);
+}
+
+#[test]
+fn rejects_unknown_formats() {
+    let output = advent(&["--format", "xml"]);
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown format `xml`"), "{stderr}");
+}
+
+#[test]
+fn checks_input_files() {
+    let path = std::env::temp_dir().join(format!("advent-2023-{}-invalid.txt", std::process::id()));
+    std::fs::write(
+        &path,
+        "Game 1: 3 blue, 4 red\nGame 2 1 blue\nGame 3: 2 green\nGame 4: 8 yellow\n",
+    )
+    .unwrap();
+    let output = advent(&["--check", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("line 2: missing `:`"), "{stderr}");
+    assert!(
+        stderr.contains("line 4: unknown color `yellow`"),
+        "{stderr}"
+    );
+
+    let output = advent(&["--check"]);
+    assert!(output.status.success());
+    assert_eq!(b"ok: 100 games\n", output.stdout.as_slice());
+}